@@ -1,3 +1,9 @@
+use std::fmt::{Display, Formatter};
+
+use crate::helpers::aho_corasick::{AhoCorasick, Match};
+use crate::helpers::wide::Wide;
+use crate::params::{Param, Params};
+
 const INPUT_FILE: &str = "input/01.txt";
 
 pub fn a() -> String {
@@ -5,101 +11,302 @@ pub fn a() -> String {
     a_with_multi_input(&input).to_string()
 }
 
-fn a_with_multi_input(input: &str) -> u64 {
-    input.lines().map(a_with_input).sum()
+pub fn a_with_multi_input(input: &str) -> Wide {
+    total(input, &Vocabulary::digits())
 }
 
-fn a_with_input(input: &str) -> u64 {
-    let mut first_char = '0';
-    let mut last_char = '0';
-    let mut set = false;
+/// What part a found on each line
+pub fn explain_a(input: &str) -> Explanation {
+    explain(input, &Vocabulary::digits())
+}
 
-    for c in input.chars().filter(|c| c.is_ascii_digit()) {
-        if !set {
-            set = true;
-            first_char = c;
-        }
+pub fn b() -> String {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    b_with_multi_input(&input).to_string()
+}
 
-        last_char = c;
-    }
+pub const PARAMS_B: &[Param] = &[Param {
+    key: "vocabulary",
+    default: "english",
+    description: "number words to look for: 'english', or a file of word=value lines",
+}];
 
-    assert!(set, "Should have found a character");
+pub fn b_with_multi_input(input: &str) -> Wide {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
+}
 
-    char_to_int(first_char) * 10 + char_to_int(last_char)
+pub fn b_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    let vocabulary = Vocabulary::load(&params.get::<String>("vocabulary")?)?;
+    Ok(total(input, &vocabulary))
 }
 
-fn char_to_int(c: char) -> u64 {
-    (c as u64) - ('0' as u64)
+/// What part b found on each line
+pub fn explain_b(input: &str, params: &Params) -> Result<Explanation, String> {
+    let vocabulary = Vocabulary::load(&params.get::<String>("vocabulary")?)?;
+    Ok(explain(input, &vocabulary))
 }
 
-pub fn b() -> String {
-    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
-    b_with_multi_input(&input).to_string()
+/// Sum of the calibration values; lines without any digits don't count (`explain` lists them).
+/// Each value can be close to 10^18 with a custom vocabulary, so this is `Wide` rather than
+/// overflowing a few dozen lines in.
+fn total(input: &str, vocabulary: &Vocabulary) -> Wide {
+    input
+        .lines()
+        .filter_map(|line| line_value(line, vocabulary))
+        .map(Wide::from)
+        .sum()
+}
+
+fn explain(input: &str, vocabulary: &Vocabulary) -> Explanation {
+    Explanation {
+        lines: input
+            .lines()
+            .map(|line| vocabulary.explain_line(line))
+            .collect(),
+    }
 }
 
-fn b_with_multi_input(input: &str) -> u64 {
-    input.lines().map(b_with_input).sum()
+/// The number words to look for (besides the digits themselves, which always count), along with
+/// their values. Words are matched case-insensitively, and can stand for more than one digit.
+#[derive(Clone, Debug)]
+pub struct Vocabulary {
+    // lowercase
+    words: Vec<(String, u64)>,
+    // finds the digits and all the words at once; patterns 0 to 9 are the digits, and the words
+    // come after, in order
+    scanner: AhoCorasick,
 }
 
-fn b_with_input(input: &str) -> u64 {
-    let chars: Vec<char> = input.chars().collect();
-    let mut first_char = 0;
-    let mut last_char = 0;
-    let mut set = false;
+/// Values are capped so a calibration value (two of them, written one after the other) fits in a
+/// u64 with room to spare
+const MAX_WORD_VALUE: u64 = 999_999_999;
+
+impl Vocabulary {
+    /// No words at all, just the digits, as in part a
+    pub fn digits() -> Self {
+        Self::from_words(Vec::new())
+    }
+
+    /// The words from the puzzle, "one" through "nine"
+    pub fn english() -> Self {
+        let words = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+
+        Self::from_words(
+            (1..)
+                .zip(words)
+                .map(|(val, w)| (w.to_string(), val))
+                .collect(),
+        )
+    }
+
+    fn from_words(words: Vec<(String, u64)>) -> Self {
+        let digits = (0..10).map(|d: u64| d.to_string());
+        let patterns: Vec<String> = digits.chain(words.iter().map(|(w, _)| w.clone())).collect();
+
+        Vocabulary {
+            words,
+            scanner: AhoCorasick::ascii_case_insensitive(&patterns),
+        }
+    }
 
-    fn get_digit(ind: usize, chars: &[char]) -> Option<u64> {
-        if ind > chars.len() {
-            return None;
+    fn value(&self, pattern: usize) -> u64 {
+        match pattern {
+            0..=9 => pattern as u64,
+            _ => self.words[pattern - 10].1,
         }
+    }
 
-        let c = chars[ind];
+    /// The first and last tokens in the line (which may be the same token, or may overlap), as
+    /// byte positions in the line; None if there aren't any. When several tokens start at the
+    /// same place, the longest one counts.
+    fn first_and_last(&self, line: &str) -> Option<(Match, Match)> {
+        // the automaton only folds ASCII, so anything else gets lowercased up front
+        if line.is_ascii() {
+            return self
+                .scanner
+                .first_match(line.as_bytes())
+                .zip(self.scanner.last_match(line.as_bytes()));
+        }
 
-        if c.is_ascii_digit() {
-            return Some(char_to_int(c));
+        // lowercasing can change the length of a character (even the number of characters, like
+        // 'İ'), so fold one character at a time and remember where each folded byte came from
+        let mut folded = String::with_capacity(line.len());
+        let mut origin: Vec<(usize, usize)> = Vec::with_capacity(line.len());
+        for (start, c) in line.char_indices() {
+            folded.extend(c.to_lowercase());
+            origin.resize(folded.len(), (start, start + c.len_utf8()));
         }
 
-        for (val, token) in [
-            (1, "one"),
-            (2, "two"),
-            (3, "three"),
-            (4, "four"),
-            (5, "five"),
-            (6, "six"),
-            (7, "seven"),
-            (8, "eight"),
-            (9, "nine"),
-        ] {
-            let token_end = ind + token.len();
-            if token_end > chars.len() {
-                continue;
-            }
+        // patterns are never empty, so every match covers at least one folded byte
+        let to_line = |m: Match| Match {
+            start: origin[m.start].0,
+            end: origin[m.end - 1].1,
+            ..m
+        };
+
+        self.scanner
+            .first_match(folded.as_bytes())
+            .zip(self.scanner.last_match(folded.as_bytes()))
+            .map(|(first, last)| (to_line(first), to_line(last)))
+    }
+
+    fn explain_line(&self, line: &str) -> Option<Calibration> {
+        let (first, last) = self.first_and_last(line)?;
+
+        let token = |m: Match| Token {
+            column: line[..m.start].chars().count() + 1,
+            text: line[m.start..m.end].to_string(),
+            value: self.value(m.pattern),
+        };
 
-            let token_chars: Vec<char> = token.chars().collect();
+        let (first, last) = (token(first), token(last));
+        let value = calibration_value(first.value, last.value);
 
-            let other_substr = &chars[ind..token_end];
+        Some(Calibration { first, last, value })
+    }
 
-            if token_chars == other_substr {
-                return Some(val);
+    /// Either the name of a built-in vocabulary, or the path to a file of them
+    pub fn load(name_or_path: &str) -> Result<Self, String> {
+        match name_or_path {
+            "english" => Ok(Self::english()),
+            path => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Cannot read vocabulary '{}': {}", path, e))?;
+                Self::parse(&text).map_err(|e| format!("In vocabulary '{}': {}", path, e))
             }
         }
-
-        None
     }
 
-    for ind in 0..chars.len() {
-        if let Some(digit) = get_digit(ind, &chars) {
-            if !set {
-                set = true;
-                first_char = digit;
+    /// Parses one "word=value" per line; blank lines and lines starting with '#' are skipped
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut words: Vec<(String, u64)> = Vec::new();
+
+        for (line_num, line) in text.lines().enumerate().map(|(i, l)| (i + 1, l.trim())) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (word, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected word=value, got '{}'", line_num, line))?;
+
+            let word = word.trim().to_lowercase();
+            if word.is_empty() {
+                return Err(format!("line {}: the word is empty", line_num));
+            }
+            if words.iter().any(|(w, _)| *w == word) {
+                return Err(format!("line {}: '{}' is listed twice", line_num, word));
             }
 
-            last_char = digit;
+            let value: u64 = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&v| v <= MAX_WORD_VALUE)
+                .ok_or_else(|| {
+                    format!(
+                        "line {}: '{}' should be a number from 0 to {}",
+                        line_num,
+                        value.trim(),
+                        MAX_WORD_VALUE
+                    )
+                })?;
+
+            words.push((word, value));
         }
+
+        Ok(Self::from_words(words))
+    }
+}
+
+/// The first and last values written one after the other, so two single digits make a two-digit
+/// number, as in the puzzle, and "twelve" then "three" makes 123
+fn calibration_value(first: u64, last: u64) -> u64 {
+    let mut shift = 10;
+    while shift <= last {
+        shift *= 10;
     }
 
-    assert!(set, "Should have found a character");
+    first * shift + last
+}
+
+/// The line's calibration value, or None if it has no digits in it
+fn line_value(line: &str, vocabulary: &Vocabulary) -> Option<u64> {
+    let (first, last) = vocabulary.first_and_last(line)?;
 
-    first_char * 10 + last_char
+    Some(calibration_value(
+        vocabulary.value(first.pattern),
+        vocabulary.value(last.pattern),
+    ))
+}
+
+/// A token which was matched on a line
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Token {
+    /// Counted in characters, from 1
+    pub column: usize,
+    /// As written on the line
+    pub text: String,
+    pub value: u64,
+}
+
+/// Where a line's calibration value came from
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Calibration {
+    pub first: Token,
+    pub last: Token,
+    pub value: u64,
+}
+
+/// What was found on each line of the input; lines without any digits are None, and count as
+/// warnings
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Explanation {
+    pub lines: Vec<Option<Calibration>>,
+}
+
+impl Explanation {
+    pub fn total(&self) -> Wide {
+        self.lines
+            .iter()
+            .flatten()
+            .map(|c| Wide::from(c.value))
+            .sum()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.lines.iter().filter(|c| c.is_none()).count()
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            match line {
+                Some(Calibration { first, last, value }) => writeln!(
+                    f,
+                    "Line {}: first '{}' at column {}, last '{}' at column {} -> {}",
+                    i + 1,
+                    first.text,
+                    first.column,
+                    last.text,
+                    last.column,
+                    value
+                )?,
+                None => writeln!(f, "Line {}: warning: no digits found; skipped", i + 1)?,
+            }
+        }
+
+        write!(
+            f,
+            "Total {} from {} lines ({} warnings)",
+            self.total(),
+            self.lines.len(),
+            self.warnings()
+        )
+    }
 }
 
 #[cfg(test)]
@@ -107,8 +314,8 @@ mod tests {
     use super::*;
 
     fn a_test(input: &str, exp: u64) {
-        let act = a_with_input(input);
-        assert_eq!(act, exp);
+        let act = line_value(input, &Vocabulary::digits());
+        assert_eq!(act, Some(exp));
     }
 
     #[test]
@@ -120,8 +327,8 @@ mod tests {
     }
 
     fn b_test(input: &str, exp: u64) {
-        let act = b_with_input(input);
-        assert_eq!(act, exp);
+        let act = line_value(input, &Vocabulary::english());
+        assert_eq!(act, Some(exp));
     }
 
     #[test]
@@ -134,4 +341,127 @@ mod tests {
         b_test("zoneight234", 14);
         b_test("7pqrstsixteen", 76);
     }
+
+    #[test]
+    fn case_insensitive() {
+        b_test("TWO1Nine", 29);
+        b_test("xEighTwo", 82);
+    }
+
+    #[test]
+    fn other_vocabularies() {
+        let german = Vocabulary::parse(
+            "# German
+eins=1
+zwei=2
+drei=3
+vier=4
+fünf=5
+sechs=6
+sieben=7
+acht=8
+neun=9
+zehn=10
+zwölf=12",
+        )
+        .unwrap();
+
+        assert_eq!(line_value("zweiundDREI", &german), Some(23));
+        assert_eq!(line_value("Fünfzehn", &german), Some(510));
+        assert_eq!(line_value("zwölf7", &german), Some(127));
+        // "one" means nothing in German
+        assert_eq!(line_value("one4acht", &german), Some(48));
+        // overlapping, as with "eightwo"
+        assert_eq!(line_value("neunzweins", &german), Some(91));
+    }
+
+    #[test]
+    fn longest_word_wins() {
+        let words = Vocabulary::parse("six=6\nsixteen=16\nteen=13").unwrap();
+        assert_eq!(line_value("sixteen", &words), Some(1613));
+        assert_eq!(line_value("six", &words), Some(66));
+    }
+
+    #[test]
+    fn bad_vocabularies() {
+        assert!(Vocabulary::parse("one").is_err());
+        assert!(Vocabulary::parse("=1").is_err());
+        assert!(Vocabulary::parse("one=uno").is_err());
+        assert!(Vocabulary::parse("one=1\nONE=1").is_err());
+        assert!(Vocabulary::parse("lots=1000000000").is_err());
+        assert!(Vocabulary::load("/no/such/vocabulary").is_err());
+    }
+
+    #[test]
+    fn explain_lines() {
+        let explanation = explain("two1nine\nno digits\nxEighTwo\n7", &Vocabulary::english());
+
+        assert_eq!(
+            explanation.lines[0],
+            Some(Calibration {
+                first: Token {
+                    column: 1,
+                    text: "two".to_string(),
+                    value: 2
+                },
+                last: Token {
+                    column: 5,
+                    text: "nine".to_string(),
+                    value: 9
+                },
+                value: 29,
+            })
+        );
+        assert_eq!(explanation.lines[1], None);
+        assert_eq!(explanation.total(), Wide::from(29 + 82 + 77));
+        assert_eq!(explanation.warnings(), 1);
+
+        assert_eq!(
+            explanation.to_string(),
+            "Line 1: first 'two' at column 1, last 'nine' at column 5 -> 29
+Line 2: warning: no digits found; skipped
+Line 3: first 'EighT' at column 2, last 'Two' at column 6 -> 82
+Line 4: first '7' at column 1, last '7' at column 1 -> 77
+Total 188 from 4 lines (1 warnings)"
+        );
+    }
+
+    #[test]
+    fn digitless_lines_are_skipped() {
+        assert_eq!(
+            a_with_multi_input("1abc2\nnothing here\ntreb7uchet"),
+            Wide::from(12 + 77)
+        );
+        assert_eq!(b_with_multi_input("nothing\ntwo1nine"), Wide::from(29));
+
+        // columns count characters, not bytes
+        let explanation = explain_a("fünf 5");
+        assert_eq!(explanation.lines[0].as_ref().unwrap().first.column, 6);
+    }
+
+    #[test]
+    fn columns_survive_case_folding() {
+        // 'İ' lowercases to two characters, which mustn't shift the columns after it
+        let explanation = explain_b("İİ Two x NINE", &Params::defaults(PARAMS_B)).unwrap();
+        let line = explanation.lines[0].as_ref().unwrap();
+        assert_eq!((line.first.column, line.first.text.as_str()), (4, "Two"));
+        assert_eq!((line.last.column, line.last.text.as_str()), (10, "NINE"));
+        assert_eq!(line.value, 29);
+
+        // and a word can match part of a character's folding; it takes the whole character
+        let vocabulary = Vocabulary::parse("i=1").unwrap();
+        let line = vocabulary.explain_line("aİb").unwrap();
+        assert_eq!((line.first.column, line.first.text.as_str()), (2, "İ"));
+    }
+
+    #[test]
+    fn huge_values_dont_overflow() {
+        // each line is worth 999999999999999999, so twenty of them overflow a u64
+        let vocabulary = Vocabulary::parse("big=999999999").unwrap();
+        let input = vec!["big"; 20].join("\n");
+
+        let expected = Wide::from(999_999_999_999_999_999_u64) * Wide::from(20);
+        assert_eq!(total(&input, &vocabulary), expected);
+        assert_eq!(explain(&input, &vocabulary).total(), expected);
+    }
 }
@@ -1,10 +1,16 @@
-use nom::branch::alt;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
 use nom::bytes::complete::tag;
-use nom::character::complete::digit1;
+use nom::character::complete::alpha1;
 use nom::combinator::map;
 use nom::multi::separated_list1;
 use nom::sequence::tuple;
-use nom::IResult;
+
+use crate::helpers::json::{json_array, json_object, json_string};
+use crate::helpers::parsing::{parse_all, unsigned, PResult};
+use crate::params::{Param, Params};
+use crate::solutions::SharedParse;
 
 const INPUT_FILE: &str = "input/02.txt";
 
@@ -13,23 +19,57 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    input
-        .lines()
-        .map(a_one_game)
-        .filter(|(_id, is_valid)| *is_valid)
-        .map(|(id, _)| id)
-        .sum()
+pub const PARAMS_A: &[Param] = &[Param {
+    key: "bag",
+    default: "12 red, 13 green, 14 blue",
+    description: "what's in the bag, like '12 red, 13 green, 14 blue'",
+}];
+
+/// The colours the puzzle's games are played with
+pub const COLOURS: &[&str] = &["red", "green", "blue"];
+
+pub fn a_with_input(input: &str) -> usize {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
 }
 
-/// Returns (id, is_valid) for the parsed game
-fn a_one_game(input: &str) -> (usize, bool) {
-    let game_record = parse_game(input);
-    let is_valid = game_record
-        .pulls
+pub fn a_with_params(input: &str, params: &Params) -> Result<usize, String> {
+    let bag = parse_bag(&params.get::<String>("bag")?)?;
+    Ok(possible_games(input, &bag)?.into_iter().sum())
+}
+
+/// IDs of the games which could have been played with the bag
+pub fn possible_games(input: &str, bag: &Cubes) -> Result<Vec<usize>, String> {
+    Ok(possible_ids(&parse_games(input)?, bag))
+}
+
+fn possible_ids(games: &[GameRecord], bag: &Cubes) -> Vec<usize> {
+    games
         .iter()
-        .all(|p| p.num_red <= 12 && p.num_green <= 13 && p.num_blue <= 14);
-    (game_record.id, is_valid)
+        .filter(|game| game.fits_in(bag))
+        .map(|game| game.id)
+        .collect()
+}
+
+/// The smallest bag which makes all the chosen games possible (or all of them, if there's no
+/// choice); errors if a chosen game isn't in the input
+pub fn minimum_bag(input: &str, ids: Option<&[usize]>) -> Result<Cubes, String> {
+    let games = parse_games(input)?;
+
+    if let Some(ids) = ids {
+        if let Some(missing) = ids.iter().find(|id| !games.iter().any(|g| g.id == **id)) {
+            return Err(format!("There is no game {}", missing));
+        }
+    }
+
+    let mut bag = Cubes::default();
+    for game in games
+        .iter()
+        .filter(|g| ids.is_none_or(|ids| ids.contains(&g.id)))
+    {
+        bag.grow_to_fit(&game.minimum_bag());
+    }
+
+    Ok(bag)
 }
 
 pub fn b() -> String {
@@ -37,140 +77,412 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    input.lines().map(game_power).sum()
+pub fn b_with_input(input: &str) -> usize {
+    b_with_params(input, &Params::defaults(&[])).expect("Default params should be valid")
+}
+
+pub fn b_with_params(input: &str, _params: &Params) -> Result<usize, String> {
+    Ok(total_power(&parse_games(input)?))
+}
+
+fn total_power(games: &[GameRecord]) -> usize {
+    games.iter().map(GameRecord::power).sum()
+}
+
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Vec<GameRecord>;
+
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+
+    fn parse(input: &str) -> Result<Vec<GameRecord>, String> {
+        parse_games(input)
+    }
+
+    fn a(games: &Vec<GameRecord>, params: &Params) -> Result<String, String> {
+        let bag = parse_bag(&params.get::<String>("bag")?)?;
+        Ok(possible_ids(games, &bag)
+            .into_iter()
+            .sum::<usize>()
+            .to_string())
+    }
+
+    fn b(games: &Vec<GameRecord>, _params: &Params) -> Result<String, String> {
+        Ok(total_power(games).to_string())
+    }
+}
+
+/// Everything about one game, rather than just what the puzzle asks for
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GameReport {
+    pub id: usize,
+    pub minimum_bag: Cubes,
+    pub power: usize,
+    /// The first pull which needed more of some colour than the bag has (counted from 1), and
+    /// which colours those were; None if the game was possible
+    pub broken_by: Option<(usize, Vec<String>)>,
+    /// (pull, colour) for each colour listed more than once in the same pull
+    pub repeats: Vec<(usize, String)>,
+}
+
+/// Every game, checked against one bag, plus totals over the whole file
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Report {
+    pub bag: Cubes,
+    pub games: Vec<GameReport>,
+    /// The most cubes of each colour seen in any one pull
+    pub max_seen: Cubes,
 }
 
-fn game_power(input: &str) -> usize {
-    let mut min_red = 0;
-    let mut min_blue = 0;
-    let mut min_green = 0;
+impl Report {
+    pub fn possible_id_sum(&self) -> usize {
+        self.possible().map(|g| g.id).sum()
+    }
+
+    pub fn total_power(&self) -> usize {
+        self.games.iter().map(|g| g.power).sum()
+    }
+
+    fn possible(&self) -> impl Iterator<Item = &GameReport> {
+        self.games.iter().filter(|g| g.broken_by.is_none())
+    }
+
+    pub fn to_table(&self) -> String {
+        let rows: Vec<[String; 5]> = self
+            .games
+            .iter()
+            .map(|g| {
+                let broken_by = match &g.broken_by {
+                    Some((pull, colours)) => format!("pull {} ({})", pull, colours.join(", ")),
+                    None => "-".to_string(),
+                };
+                let repeats = if g.repeats.is_empty() {
+                    "-".to_string()
+                } else {
+                    g.repeats
+                        .iter()
+                        .map(|(pull, colour)| format!("pull {}: {}", pull, colour))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+
+                [
+                    g.id.to_string(),
+                    g.minimum_bag.to_string(),
+                    g.power.to_string(),
+                    broken_by,
+                    repeats,
+                ]
+            })
+            .collect();
+
+        let header = ["Game", "Minimum bag", "Power", "Broken by", "Repeats"].map(String::from);
+
+        let mut widths = header.clone().map(|h| h.len());
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let format_row = |row: &[String; 5]| {
+            // numbers on the right, words on the left
+            format!(
+                "{:>w0$} | {:<w1$} | {:>w2$} | {:<w3$} | {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            )
+        };
+
+        let mut out = format!("Bag: {}\n", self.bag);
+        out.push_str(&format_row(&header));
+        out.push('\n');
+        for row in rows.iter() {
+            out.push_str(&format_row(row));
+            out.push('\n');
+        }
+
+        out.push_str(&format!("Max seen: {}\n", self.max_seen));
+        out.push_str(&format!(
+            "Possible: {} of {} games, IDs summing to {}\n",
+            self.possible().count(),
+            self.games.len(),
+            self.possible_id_sum()
+        ));
+        out.push_str(&format!("Total power: {}", self.total_power()));
+
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let games = self.games.iter().map(|g| {
+            let broken_by = match &g.broken_by {
+                Some((pull, colours)) => json_object([
+                    ("pull", pull.to_string()),
+                    (
+                        "colours",
+                        json_array(colours.iter().map(|c| json_string(c))),
+                    ),
+                ]),
+                None => "null".to_string(),
+            };
+            let repeats = g.repeats.iter().map(|(pull, colour)| {
+                json_object([("pull", pull.to_string()), ("colour", json_string(colour))])
+            });
+
+            json_object([
+                ("id", g.id.to_string()),
+                ("minimum_bag", g.minimum_bag.to_json()),
+                ("power", g.power.to_string()),
+                ("possible", g.broken_by.is_none().to_string()),
+                ("broken_by", broken_by),
+                ("repeats", json_array(repeats)),
+            ])
+        });
+
+        json_object([
+            ("bag", self.bag.to_json()),
+            ("games", json_array(games)),
+            ("max_seen", self.max_seen.to_json()),
+            ("possible_id_sum", self.possible_id_sum().to_string()),
+            ("total_power", self.total_power().to_string()),
+        ])
+    }
+}
+
+/// Checks every game against the bag; power is always over the puzzle's `COLOURS`, as in part b,
+/// whatever the bag holds
+pub fn report(input: &str, bag: &Cubes) -> Result<Report, String> {
+    let mut max_seen = Cubes::default();
+
+    let games = input
+        .lines()
+        .map(|line| {
+            let game = parse_game(line)?;
+
+            let minimum_bag = game.minimum_bag();
+            max_seen.grow_to_fit(&minimum_bag);
+
+            let broken_by = game.pulls.iter().enumerate().find_map(|(i, pull)| {
+                let over: Vec<String> = pull
+                    .cubes
+                    .counts
+                    .iter()
+                    .filter(|(colour, &count)| count > bag.get(colour))
+                    .map(|(colour, _)| colour.clone())
+                    .collect();
+
+                (!over.is_empty()).then_some((i + 1, over))
+            });
+
+            let repeats = game
+                .pulls
+                .iter()
+                .enumerate()
+                .flat_map(|(i, pull)| pull.repeated.iter().map(move |c| (i + 1, c.clone())))
+                .collect();
+
+            Ok(GameReport {
+                id: game.id,
+                power: minimum_bag.power(COLOURS),
+                minimum_bag,
+                broken_by,
+                repeats,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(Report {
+        bag: bag.clone(),
+        games,
+        max_seen,
+    })
+}
+
+/// Numbers of cubes, by colour; any colour at all, and colours which aren't mentioned count as
+/// zero
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Cubes {
+    counts: BTreeMap<String, usize>,
+}
+
+impl Cubes {
+    pub fn get(&self, colour: &str) -> usize {
+        self.counts.get(colour).copied().unwrap_or(0)
+    }
+
+    /// Whether there are at least this many of every colour in the bag
+    pub fn fits_in(&self, bag: &Cubes) -> bool {
+        self.counts
+            .iter()
+            .all(|(colour, &count)| count <= bag.get(colour))
+    }
+
+    /// Adds cubes to this bag until the other one fits in it
+    pub fn grow_to_fit(&mut self, other: &Cubes) {
+        for (colour, &count) in other.counts.iter() {
+            let mine = self.counts.entry(colour.clone()).or_insert(0);
+            *mine = (*mine).max(count);
+        }
+    }
 
-    for pull in parse_game(input).pulls {
-        min_red = min_red.max(pull.num_red);
-        min_blue = min_blue.max(pull.num_blue);
-        min_green = min_green.max(pull.num_green);
+    /// Product of the counts over the given colours, so one which never turns up makes it zero
+    pub fn power(&self, colours: &[&str]) -> usize {
+        colours.iter().map(|colour| self.get(colour)).product()
     }
+}
 
-    min_red * min_blue * min_green
+impl Cubes {
+    fn to_json(&self) -> String {
+        json_object(self.counts.iter().map(|(c, n)| (c.as_str(), n.to_string())))
+    }
+}
+
+impl Display for Cubes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (colour, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, colour)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
-struct GameRecord {
+pub struct GameRecord {
     id: usize,
     pulls: Vec<Pull>,
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct Pull {
-    num_red: usize,
-    num_blue: usize,
-    num_green: usize,
-}
-
-fn parse_game(input: &str) -> GameRecord {
-    enum Color {
-        Blue,
-        Red,
-        Green,
-    }
-
-    /// Parses things like "green"
-    fn parse_color(input: &str) -> IResult<&str, Color> {
-        alt((
-            map(tag("blue"), |_| Color::Blue),
-            map(tag("green"), |_| Color::Green),
-            map(tag("red"), |_| Color::Red),
-        ))(input)
-    }
-
-    /// Parses things like "3 red"
-    fn parse_color_num(input: &str) -> IResult<&str, (usize, Color)> {
-        map(
-            tuple((digit1, tag(" "), parse_color)),
-            |(digits, _, color)| (digits.parse::<usize>().expect("digits should parse"), color),
-        )(input)
-    }
-
-    /// Parses things like "3 blue, 4 red"
-    fn parse_pull(input: &str) -> IResult<&str, Pull> {
-        let (input, colors) = separated_list1(tag(", "), parse_color_num)(input)?;
-
-        let mut num_red = 0;
-        let mut num_blue = 0;
-        let mut num_green = 0;
-
-        for (amt, color) in colors {
-            match color {
-                Color::Blue => {
-                    num_blue = amt;
-                }
-                Color::Green => {
-                    num_green = amt;
-                }
-                Color::Red => {
-                    num_red = amt;
-                }
-            }
+    cubes: Cubes,
+    /// Colours which were listed more than once; the last count listed is the one in `cubes`
+    repeated: Vec<String>,
+}
+
+impl GameRecord {
+    /// The fewest cubes of each colour that the game could have been played with
+    fn minimum_bag(&self) -> Cubes {
+        let mut bag = Cubes::default();
+        for pull in self.pulls.iter() {
+            bag.grow_to_fit(&pull.cubes);
         }
+        bag
+    }
 
-        let pull = Pull {
-            num_green,
-            num_red,
-            num_blue,
-        };
+    /// Whether every pull could have come out of the bag
+    fn fits_in(&self, bag: &Cubes) -> bool {
+        self.pulls.iter().all(|p| p.cubes.fits_in(bag))
+    }
+
+    fn power(&self) -> usize {
+        self.minimum_bag().power(COLOURS)
+    }
+}
+
+/// Parses things like "3 red"
+fn parse_colour_num(input: &str) -> PResult<'_, (usize, &str)> {
+    map(tuple((unsigned, tag(" "), alpha1)), |(amt, _, colour)| {
+        (amt, colour)
+    })(input)
+}
+
+/// Parses things like "3 blue, 4 red"
+fn parse_pull(input: &str) -> PResult<'_, Pull> {
+    let (input, colours) = separated_list1(tag(", "), parse_colour_num)(input)?;
 
-        Ok((input, pull))
+    let mut cubes = Cubes::default();
+    let mut repeated = Vec::new();
+
+    for (amt, colour) in colours {
+        if cubes.counts.insert(colour.to_string(), amt).is_some()
+            && !repeated.iter().any(|r| r == colour)
+        {
+            repeated.push(colour.to_string());
+        }
     }
 
+    Ok((input, Pull { cubes, repeated }))
+}
+
+/// Parses a bag given on the command line, like "12 red, 13 green, 14 blue"
+pub fn parse_bag(input: &str) -> Result<Cubes, String> {
+    let pull = parse_all(input.trim(), parse_pull)
+        .map_err(|e| format!("Cannot parse bag '{}': {}", input, e))?;
+
+    match pull.repeated.first() {
+        Some(colour) => Err(format!("The bag lists '{}' more than once", colour)),
+        None => Ok(pull.cubes),
+    }
+}
+
+fn parse_games(input: &str) -> Result<Vec<GameRecord>, String> {
+    input.lines().map(parse_game).collect()
+}
+
+fn parse_game(input: &str) -> Result<GameRecord, String> {
     /// Parses the whole line
-    fn parse_helper(input: &str) -> IResult<&str, GameRecord> {
+    fn parse_helper(input: &str) -> PResult<'_, GameRecord> {
         let (input, _) = tag("Game ")(input)?;
-        let (input, id_digits) = digit1(input)?;
-        let id: usize = id_digits.parse().expect("Digits should be a usize");
+        let (input, id) = unsigned(input)?;
         let (input, _) = tag(": ")(input)?;
         let (input, pulls) = separated_list1(tag("; "), parse_pull)(input)?;
         Ok((input, GameRecord { id, pulls }))
     }
 
-    let parsed = parse_helper(input).expect("Input should be parseable");
-    assert_eq!(parsed.0, "");
-    parsed.1
+    parse_all(input, parse_helper).map_err(|e| format!("Cannot parse game '{}': {}", input, e))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn cubes(counts: &[(&str, usize)]) -> Cubes {
+        Cubes {
+            counts: counts.iter().map(|&(c, n)| (c.to_string(), n)).collect(),
+        }
+    }
+
+    fn pull(counts: &[(&str, usize)]) -> Pull {
+        Pull {
+            cubes: cubes(counts),
+            repeated: Vec::new(),
+        }
+    }
+
     #[test]
     fn test_parser() {
         let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green";
         let exp = GameRecord {
             id: 1,
             pulls: vec![
-                Pull {
-                    num_blue: 3,
-                    num_red: 4,
-                    num_green: 0,
-                },
-                Pull {
-                    num_red: 1,
-                    num_green: 2,
-                    num_blue: 6,
-                },
-                Pull {
-                    num_green: 2,
-                    num_red: 0,
-                    num_blue: 0,
-                },
+                pull(&[("blue", 3), ("red", 4)]),
+                pull(&[("red", 1), ("green", 2), ("blue", 6)]),
+                pull(&[("green", 2)]),
             ],
         };
 
-        assert_eq!(parse_game(input), exp);
+        assert_eq!(parse_game(input).unwrap(), exp);
+
+        let game = parse_game("Game 7: 2 purple, 1 red; 5 purple").unwrap();
+        assert_eq!(game.minimum_bag(), cubes(&[("purple", 5), ("red", 1)]));
     }
 
     fn game_test(input: &str, exp: (usize, bool)) {
-        let act = a_one_game(input);
+        let bag = parse_bag("12 red, 13 green, 14 blue").unwrap();
+        let game = parse_game(input).unwrap();
+        let act = (game.id, game.fits_in(&bag));
         assert_eq!(act.0, exp.0, "ID should parse");
         assert_eq!(act.1, exp.1, "Validity should check correctly");
     }
@@ -211,7 +523,7 @@ mod tests {
     }
 
     fn game_power_test(input: &str, exp: usize) {
-        let act = game_power(input);
+        let act = parse_game(input).unwrap().power();
         assert_eq!(act, exp);
     }
 
@@ -238,4 +550,140 @@ mod tests {
             game_power_test(input, exp);
         }
     }
+
+    #[test]
+    fn missing_colour_has_no_power() {
+        // no blue at all, so the minimum bag has zero blue cubes
+        game_power_test("Game 7: 3 red, 2 green; 1 green", 0);
+        assert_eq!(
+            b_with_input("Game 7: 3 red, 2 green; 1 green\nGame 8: 1 red, 1 green, 1 blue"),
+            1
+        );
+    }
+
+    const SAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn bag_queries() {
+        assert_eq!(
+            possible_games(SAMPLE, &parse_bag("12 red, 13 green, 14 blue").unwrap()).unwrap(),
+            vec![1, 2, 5]
+        );
+        assert_eq!(
+            possible_games(SAMPLE, &parse_bag("20 red, 13 green, 6 blue").unwrap()).unwrap(),
+            vec![1, 2, 3, 5]
+        );
+        // no blue at all
+        assert_eq!(
+            possible_games(SAMPLE, &parse_bag("100 red, 100 green").unwrap()).unwrap(),
+            Vec::<usize>::new()
+        );
+
+        let bag = minimum_bag(SAMPLE, Some(&[1, 5])).unwrap();
+        assert_eq!(bag.to_string(), "6 blue, 3 green, 6 red");
+        assert_eq!(possible_games(SAMPLE, &bag), Ok(vec![1, 2, 5]));
+
+        let bag = minimum_bag(SAMPLE, None).unwrap();
+        assert_eq!(bag.to_string(), "15 blue, 13 green, 20 red");
+
+        assert!(minimum_bag(SAMPLE, Some(&[6])).is_err());
+        assert!(parse_bag("twelve red").is_err());
+    }
+
+    #[test]
+    fn bag_param() {
+        let params = Params::with_overrides(
+            PARAMS_A,
+            &[("bag".to_string(), "20 red, 13 green, 6 blue".to_string())],
+        );
+        assert_eq!(a_with_params(SAMPLE, &params), Ok(11));
+        assert_eq!(a_with_input(SAMPLE), 8);
+    }
+
+    #[test]
+    fn repeated_colours() {
+        let game = parse_game("Game 9: 1 red, 2 blue, 3 red; 4 green, 5 green, 6 green").unwrap();
+        assert_eq!(game.pulls[0].cubes, cubes(&[("blue", 2), ("red", 3)]));
+        assert_eq!(game.pulls[0].repeated, vec!["red".to_string()]);
+        assert_eq!(game.pulls[1].repeated, vec!["green".to_string()]);
+
+        assert!(parse_bag("12 red, 1 red").is_err());
+    }
+
+    #[test]
+    fn report_table() {
+        let input = format!("{}\nGame 6: 1 red, 2 red", SAMPLE);
+        let report = report(&input, &parse_bag("12 red, 13 green, 14 blue").unwrap()).unwrap();
+
+        assert_eq!(
+            report.games[2].broken_by,
+            Some((1, vec!["red".to_string()]))
+        );
+        assert_eq!(
+            report.games[3].broken_by,
+            Some((3, vec!["blue".to_string(), "red".to_string()]))
+        );
+        assert_eq!(report.games[5].repeats, vec![(1, "red".to_string())]);
+        assert_eq!(report.possible_id_sum(), 1 + 2 + 5 + 6);
+
+        assert_eq!(
+            report.to_table(),
+            "Bag: 14 blue, 13 green, 12 red
+Game | Minimum bag              | Power | Broken by          | Repeats
+   1 | 6 blue, 2 green, 4 red   |    48 | -                  | -
+   2 | 4 blue, 3 green, 1 red   |    12 | -                  | -
+   3 | 6 blue, 13 green, 20 red |  1560 | pull 1 (red)       | -
+   4 | 15 blue, 3 green, 14 red |   630 | pull 3 (blue, red) | -
+   5 | 2 blue, 3 green, 6 red   |    36 | -                  | -
+   6 | 2 red                    |     0 | -                  | pull 1: red
+Max seen: 15 blue, 13 green, 20 red
+Possible: 4 of 6 games, IDs summing to 14
+Total power: 2286"
+        );
+    }
+
+    #[test]
+    fn report_json() {
+        let report = report(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            &parse_bag("5 blue, 5 red, 5 green").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            report.to_json(),
+            r#"{"bag":{"blue":5,"green":5,"red":5},"games":[{"id":1,"minimum_bag":{"blue":6,"green":2,"red":4},"power":48,"possible":false,"broken_by":{"pull":2,"colours":["blue"]},"repeats":[]}],"max_seen":{"blue":6,"green":2,"red":4},"possible_id_sum":0,"total_power":48}"#
+        );
+    }
+
+    #[test]
+    fn report_agrees_with_part_b() {
+        // the bag mentions purple and leaves out green, but power is still red * green * blue
+        let bag = parse_bag("12 red, 14 blue, 3 purple").unwrap();
+        let report = report(SAMPLE, &bag).unwrap();
+        assert_eq!(report.games[0].power, 48);
+        assert_eq!(report.total_power(), b_with_input(SAMPLE));
+    }
+
+    #[test]
+    fn bad_games() {
+        let input = format!("{}\nGame six: 1 red", SAMPLE);
+        let bag = parse_bag("12 red, 13 green, 14 blue").unwrap();
+
+        for err in [
+            report(&input, &bag).map(|_| ()),
+            possible_games(&input, &bag).map(|_| ()),
+            minimum_bag(&input, None).map(|_| ()),
+            b_with_params(&input, &Params::defaults(&[])).map(|_| ()),
+            Solver::parse(&input).map(|_| ()),
+        ] {
+            assert!(err
+                .unwrap_err()
+                .starts_with("Cannot parse game 'Game six: 1 red'"));
+        }
+    }
 }
@@ -1,4 +1,9 @@
-use ahash::HashSet;
+use std::ops::Range;
+
+use crate::helpers::grid::Grid;
+use crate::params::{Param, Params};
+use crate::render::{Canvas, Colour};
+use crate::solutions::SharedParse;
 
 const INPUT_FILE: &str = "input/03.txt";
 
@@ -7,58 +12,43 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> u64 {
-    // this is the worst thing i've ever written, maybe :thinking:
-    let adj_locations: HashSet<(usize, usize)> = input
-        // go through each line and collect all the coordinates of special characters ...
-        .lines()
-        .enumerate()
-        .flat_map(|(row_ind, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|&(_, c)| !c.is_alphanumeric() && !(c == '.'))
-                .map(move |(col_ind, _)| (row_ind as i32, col_ind as i32))
-        })
-        // ... then for each coordinate of same, emit every coordinate adjacent to any of those ...
-        .flat_map(|(row, col)| {
-            (-1..=1).flat_map(move |dx| (-1..=1).map(move |dy| (row + dy, col + dx)))
-        })
-        .filter(|&(row, col)| row >= 0 && col >= 0)
-        .map(|(row, col)| (row as usize, col as usize))
-        // ... and collection the result as a hashset
-        .collect();
-
-    let mut total_adj = 0;
-
-    for (row_ind, line) in input.lines().enumerate() {
-        let mut is_running = false;
-        let mut is_adj = false;
-        let mut running_total = 0;
-
-        for (col_ind, c) in line.chars().enumerate() {
-            if c.is_numeric() {
-                if adj_locations.contains(&(row_ind, col_ind)) {
-                    is_adj = true;
-                }
-                is_running = true;
-                running_total = running_total * 10 + ((c as u64) - ('0' as u64));
-            } else {
-                if is_running && is_adj {
-                    total_adj += running_total;
-                }
+pub const PARAMS_A: &[Param] = &[Param {
+    key: "symbols",
+    default: "any",
+    description: "which symbols make a part number: 'any', or the symbol characters themselves",
+}];
 
-                is_running = false;
-                is_adj = false;
-                running_total = 0;
-            }
-        }
+pub fn a_with_input(input: &str) -> u64 {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
+}
 
-        if is_running && is_adj {
-            total_adj += running_total;
-        }
+pub fn a_with_params(input: &str, params: &Params) -> Result<u64, String> {
+    let symbols = SymbolSet::parse(&params.get::<String>("symbols")?)?;
+    SchematicGraph::parse(input)?.part_number_sum(&symbols)
+}
+
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = SchematicGraph;
+
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+    const PARAMS_B: &'static [Param] = PARAMS_B;
+
+    fn parse(input: &str) -> Result<SchematicGraph, String> {
+        SchematicGraph::parse(input)
     }
 
-    total_adj
+    fn a(graph: &SchematicGraph, params: &Params) -> Result<String, String> {
+        let symbols = SymbolSet::parse(&params.get::<String>("symbols")?)?;
+        graph.part_number_sum(&symbols).map(|n| n.to_string())
+    }
+
+    fn b(graph: &SchematicGraph, params: &Params) -> Result<String, String> {
+        graph
+            .gear_ratio_sum(&gear_rule(params)?)
+            .map(|n| n.to_string())
+    }
 }
 
 pub fn b() -> String {
@@ -66,83 +56,377 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> u64 {
-    // this is a little less bad than the previous
-    let gear_adjacency: Vec<HashSet<(usize, usize)>> = input
-        // go through each line and collect all the coordinates of gears ...
-        .lines()
-        .enumerate()
-        .flat_map(|(row_ind, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|&(_, c)| c == '*')
-                .map(move |(col_ind, _)| (row_ind as i32, col_ind as i32))
-        })
-        // ... then for each coordinate of same, emit every coordinate adjacent to any of those ...
-        .map(|(row, col)| {
-            (-1..=1)
-                .flat_map(move |dx| (-1..=1).map(move |dy| (row + dy, col + dx)))
-                .filter(|&(r, c)| r >= 0 && c >= 0)
-                .map(|(r, c)| (r as usize, c as usize))
-                .collect::<HashSet<_>>()
-        })
-        // then just blob them up into a list of sets
-        .collect();
+pub const PARAMS_B: &[Param] = &[
+    Param {
+        key: "gear",
+        default: "*",
+        description: "which symbols can be gears: 'any', or the symbol characters themselves",
+    },
+    Param {
+        key: "neighbours",
+        default: "2",
+        description: "how many numbers a gear touches",
+    },
+];
 
-    let mut gear_parts: Vec<Vec<u64>> = vec![Vec::new(); gear_adjacency.len()];
+pub fn b_with_input(input: &str) -> u64 {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
+}
 
-    for (row_ind, line) in input.lines().enumerate() {
-        let mut is_running = false;
-        let mut adj_gears: HashSet<usize> = HashSet::default();
-        let mut running_total = 0;
+pub fn b_with_params(input: &str, params: &Params) -> Result<u64, String> {
+    let rule = gear_rule(params)?;
+    SchematicGraph::parse(input)?.gear_ratio_sum(&rule)
+}
 
-        for (col_ind, c) in line.chars().enumerate() {
-            if c.is_numeric() {
-                for (gear_ind, adj_set) in gear_adjacency.iter().enumerate() {
-                    if adj_set.contains(&(row_ind, col_ind)) {
-                        adj_gears.insert(gear_ind);
-                    }
-                }
+fn gear_rule(params: &Params) -> Result<GearRule, String> {
+    let rule = GearRule {
+        symbols: SymbolSet::parse(&params.get::<String>("gear")?)?,
+        neighbours: params.get("neighbours")?,
+    };
+    if rule.neighbours == 0 {
+        return Err("A gear should touch at least one number".to_string());
+    }
+    Ok(rule)
+}
+
+pub fn dot() -> String {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    SchematicGraph::parse(&input)
+        .expect("Input should parse")
+        .to_dot()
+}
 
-                is_running = true;
-                running_total = running_total * 10 + ((c as u64) - ('0' as u64));
+fn is_symbol(c: char) -> bool {
+    !c.is_alphanumeric() && c != '.'
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Cell {
+    Blank,
+    Digit(u8),
+    Symbol(char),
+}
+
+/// A run of digits along one row
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NumberSpan {
+    pub value: u64,
+    pub row: usize,
+    pub columns: Range<usize>,
+}
+
+struct Schematic {
+    grid: Grid<Cell>,
+    numbers: Vec<NumberSpan>,
+    // which number covers each cell (as an index into `numbers`), so finding the numbers next to
+    // a symbol is just a look at its neighbours
+    number_at: Grid<Option<usize>>,
+}
+
+impl Schematic {
+    /// One pass over the grid, labelling the numbers as it goes
+    fn parse(input: &str) -> Result<Self, String> {
+        let grid: Grid<Cell> = Grid::parse(input, |c| {
+            Some(if let Some(d) = c.to_digit(10) {
+                Cell::Digit(d as u8)
+            } else if is_symbol(c) {
+                Cell::Symbol(c)
             } else {
-                if is_running {
-                    for gear_ind in adj_gears.iter().copied() {
-                        gear_parts[gear_ind].push(running_total);
+                Cell::Blank
+            })
+        })
+        .map_err(|e| format!("Input should be a rectangular grid: {}", e))?;
+
+        let mut numbers: Vec<NumberSpan> = Vec::new();
+        let mut number_at: Grid<Option<usize>> = Grid::new(grid.width(), grid.height(), None);
+
+        for y in 0..grid.height() {
+            let mut running: Option<usize> = None;
+
+            for (x, cell) in grid.row(y).iter().enumerate() {
+                running = match (*cell, running) {
+                    (Cell::Digit(d), Some(ind)) => {
+                        let number = &mut numbers[ind];
+                        number.value = number
+                            .value
+                            .checked_mul(10)
+                            .and_then(|v| v.checked_add(d as u64))
+                            .ok_or_else(|| {
+                                format!(
+                                    "The number at row {}, column {} is too big",
+                                    y, number.columns.start
+                                )
+                            })?;
+                        number.columns.end = x + 1;
+                        Some(ind)
                     }
+                    (Cell::Digit(d), None) => {
+                        numbers.push(NumberSpan {
+                            value: d as u64,
+                            row: y,
+                            columns: x..x + 1,
+                        });
+                        Some(numbers.len() - 1)
+                    }
+                    _ => None,
+                };
+
+                number_at[(x, y)] = running;
+            }
+        }
+
+        Ok(Schematic {
+            grid,
+            numbers,
+            number_at,
+        })
+    }
+
+    fn symbols(&self) -> impl Iterator<Item = ((usize, usize), char)> + '_ {
+        self.grid.iter().filter_map(|(pos, cell)| match cell {
+            Cell::Symbol(c) => Some((pos, *c)),
+            _ => None,
+        })
+    }
+
+    /// The distinct numbers touching the position (diagonals included), as indices into `numbers`
+    fn adjacent_numbers(&self, pos: (usize, usize)) -> Vec<usize> {
+        let mut found: Vec<usize> = Vec::new();
+
+        for neighbour in self.grid.neighbours_8(pos) {
+            if let Some(ind) = self.number_at[neighbour] {
+                // at most eight neighbours, so a linear scan beats hashing
+                if !found.contains(&ind) {
+                    found.push(ind);
                 }
+            }
+        }
+
+        found
+    }
+}
+
+/// Which symbols a query is about
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum SymbolSet {
+    Any,
+    Only(Vec<char>),
+}
+
+impl SymbolSet {
+    /// "any", or else the symbols themselves, like "*#"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text == "any" {
+            return Ok(SymbolSet::Any);
+        }
+
+        let symbols: Vec<char> = text.chars().collect();
+        if symbols.is_empty() {
+            return Err("The symbol set should be 'any', or some symbols".to_string());
+        }
+        if let Some(bad) = symbols.iter().find(|&&c| !is_symbol(c)) {
+            return Err(format!("'{}' is not a symbol", bad));
+        }
+
+        Ok(SymbolSet::Only(symbols))
+    }
 
-                is_running = false;
-                adj_gears.clear();
-                running_total = 0;
+    fn contains(&self, c: char) -> bool {
+        match self {
+            SymbolSet::Any => true,
+            SymbolSet::Only(symbols) => symbols.contains(&c),
+        }
+    }
+}
+
+/// A generalised gear: one of the given symbols, touching exactly this many numbers
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GearRule {
+    pub symbols: SymbolSet,
+    pub neighbours: usize,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Symbol {
+    pub c: char,
+    /// (x, y)
+    pub pos: (usize, usize),
+}
+
+/// The schematic as a bipartite graph: numbers on one side, symbols on the other, and an edge
+/// wherever a number touches a symbol (diagonals included)
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct SchematicGraph {
+    pub numbers: Vec<NumberSpan>,
+    pub symbols: Vec<Symbol>,
+    // for each symbol, the numbers it touches, as indices into `numbers`
+    symbol_edges: Vec<Vec<usize>>,
+    // and the other way round
+    number_edges: Vec<Vec<usize>>,
+}
+
+impl SchematicGraph {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let schematic = Schematic::parse(input)?;
+
+        let symbols: Vec<Symbol> = schematic
+            .symbols()
+            .map(|(pos, c)| Symbol { c, pos })
+            .collect();
+
+        let symbol_edges: Vec<Vec<usize>> = symbols
+            .iter()
+            .map(|symbol| schematic.adjacent_numbers(symbol.pos))
+            .collect();
+
+        let mut number_edges = vec![Vec::new(); schematic.numbers.len()];
+        for (symbol_ind, numbers) in symbol_edges.iter().enumerate() {
+            for &number_ind in numbers {
+                number_edges[number_ind].push(symbol_ind);
             }
         }
 
-        if is_running {
-            for gear_ind in adj_gears.iter().copied() {
-                gear_parts[gear_ind].push(running_total);
+        Ok(SchematicGraph {
+            numbers: schematic.numbers,
+            symbols,
+            symbol_edges,
+            number_edges,
+        })
+    }
+
+    /// The numbers touching the symbol
+    pub fn numbers_around(&self, symbol_ind: usize) -> impl Iterator<Item = &NumberSpan> {
+        self.symbol_edges[symbol_ind]
+            .iter()
+            .map(|&ind| &self.numbers[ind])
+    }
+
+    /// The symbols touching the number
+    pub fn symbols_around(&self, number_ind: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_edges[number_ind]
+            .iter()
+            .map(|&ind| &self.symbols[ind])
+    }
+
+    /// For each number, whether it touches any of the symbols
+    pub fn part_numbers(&self, symbols: &SymbolSet) -> Vec<bool> {
+        (0..self.numbers.len())
+            .map(|ind| self.symbols_around(ind).any(|s| symbols.contains(s.c)))
+            .collect()
+    }
+
+    /// Errors if the sum doesn't fit in a u64
+    pub fn part_number_sum(&self, symbols: &SymbolSet) -> Result<u64, String> {
+        self.numbers
+            .iter()
+            .zip(self.part_numbers(symbols))
+            .filter(|(_, is_part)| *is_part)
+            .try_fold(0u64, |acc, (number, _)| acc.checked_add(number.value))
+            .ok_or_else(|| "The part numbers add up to more than fits in a u64".to_string())
+    }
+
+    /// Sum, over every symbol matching the rule, of the product of the numbers around it; errors
+    /// if any of that doesn't fit in a u64
+    pub fn gear_ratio_sum(&self, rule: &GearRule) -> Result<u64, String> {
+        (0..self.symbols.len())
+            .filter(|&ind| rule.symbols.contains(self.symbols[ind].c))
+            .filter(|&ind| self.symbol_edges[ind].len() == rule.neighbours)
+            .try_fold(0u64, |acc, ind| {
+                let (x, y) = self.symbols[ind].pos;
+                let ratio = self
+                    .numbers_around(ind)
+                    .try_fold(1u64, |acc, n| acc.checked_mul(n.value))
+                    .ok_or_else(|| {
+                        format!("The gear ratio at row {}, column {} is too big", y, x)
+                    })?;
+
+                acc.checked_add(ratio)
+                    .ok_or_else(|| "The gear ratios add up to more than fits in a u64".to_string())
+            })
+    }
+
+    /// The graph in Graphviz's DOT language; numbers are boxes and symbols are circles, and each
+    /// is labelled with where it is in the schematic
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("graph schematic {\n");
+
+        for (ind, number) in self.numbers.iter().enumerate() {
+            out.push_str(&format!(
+                "    n{} [shape=box, label=\"{}\\n(row {}, cols {}-{})\"];\n",
+                ind,
+                number.value,
+                number.row,
+                number.columns.start,
+                number.columns.end - 1
+            ));
+        }
+
+        for (ind, symbol) in self.symbols.iter().enumerate() {
+            out.push_str(&format!(
+                "    s{} [shape=circle, label=\"{}\\n(row {}, col {})\"];\n",
+                ind,
+                dot_escape(symbol.c),
+                symbol.pos.1,
+                symbol.pos.0
+            ));
+        }
+
+        for (symbol_ind, numbers) in self.symbol_edges.iter().enumerate() {
+            for number_ind in numbers {
+                out.push_str(&format!("    s{} -- n{};\n", symbol_ind, number_ind));
             }
         }
+
+        out.push('}');
+        out
     }
+}
 
-    // finally, add up the derived power of all the gears
-    let mut actual_total = 0;
+/// A symbol, made safe to go inside a quoted DOT label
+fn dot_escape(c: char) -> String {
+    match c {
+        '"' | '\\' => format!("\\{}", c),
+        c => c.to_string(),
+    }
+}
 
-    for gear_part in gear_parts.into_iter() {
-        if gear_part.len() == 2 {
-            actual_total += gear_part[0] * gear_part[1];
+pub fn render() -> Canvas {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    render_with_input(&input)
+}
+
+fn render_with_input(input: &str) -> Canvas {
+    let graph = SchematicGraph::parse(input).expect("Input should parse");
+
+    let mut canvas = Canvas::from_text(input);
+
+    for symbol in graph.symbols.iter() {
+        canvas.paint(symbol.pos.0, symbol.pos.1, Colour::Yellow);
+    }
+
+    let is_part = graph.part_numbers(&SymbolSet::Any);
+    for (number, is_part) in graph.numbers.iter().zip(is_part) {
+        for x in number.columns.clone() {
+            if is_part {
+                canvas.paint(x, number.row, Colour::Green);
+            } else {
+                canvas.mark(x, number.row, Colour::Red, 'x');
+            }
         }
     }
 
-    actual_total
+    canvas.add_legend(Colour::Yellow, None, "symbol");
+    canvas.add_legend(Colour::Green, None, "part number");
+    canvas.add_legend(Colour::Red, Some('x'), "not adjacent to any symbol");
+
+    canvas
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_A: &'static str = "467..114..
+    const SAMPLE_A: &str = "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -172,4 +456,142 @@ mod tests {
     fn sample_b_test() {
         assert_eq!(b_with_input(SAMPLE_A), 467 * 35 + 755 * 598);
     }
+
+    #[test]
+    fn numbers_at_the_edges() {
+        // numbers running into the right edge, and gears in the corners
+        let input = "*12
+3..
+.*4";
+        assert_eq!(a_with_input(input), 12 + 3 + 4);
+        assert_eq!(b_with_input(input), 12 * 3 + 3 * 4);
+    }
+
+    #[test]
+    fn wide_generated_schematic() {
+        // "12*34." over and over, thousands of cells wide; every gear touches exactly two numbers
+        let repeats = 2000;
+        let row = "12*34.".repeat(repeats);
+        let blank = ".".repeat(row.len());
+        let input = format!("{}\n{}\n{}", blank, row, blank);
+
+        assert_eq!(a_with_input(&input), (12 + 34) * repeats as u64);
+        assert_eq!(b_with_input(&input), 12 * 34 * repeats as u64);
+    }
+
+    #[test]
+    fn generalised_gears() {
+        let input = "
+10.....
+..#.20.
+.5.....
+..*3...
+.7.....
+.....$1"
+            .trim();
+        let graph = SchematicGraph::parse(input).unwrap();
+
+        let hashes = |neighbours| GearRule {
+            symbols: SymbolSet::parse("#").unwrap(),
+            neighbours,
+        };
+        assert_eq!(graph.gear_ratio_sum(&hashes(2)), Ok(10 * 5));
+        assert_eq!(graph.gear_ratio_sum(&hashes(3)), Ok(0));
+
+        // the '*' touches 5, 3 and 7
+        let three = GearRule {
+            symbols: SymbolSet::parse("*").unwrap(),
+            neighbours: 3,
+        };
+        assert_eq!(graph.gear_ratio_sum(&three), Ok(5 * 3 * 7));
+
+        let any_one = GearRule {
+            symbols: SymbolSet::Any,
+            neighbours: 1,
+        };
+        assert_eq!(graph.gear_ratio_sum(&any_one), Ok(1));
+
+        assert_eq!(
+            graph.part_number_sum(&SymbolSet::Any),
+            Ok(10 + 5 + 3 + 7 + 1)
+        );
+        assert_eq!(
+            graph.part_number_sum(&SymbolSet::parse("$#").unwrap()),
+            Ok(10 + 5 + 1)
+        );
+        assert_eq!(
+            graph.symbols_around(2).map(|s| s.c).collect::<Vec<_>>(),
+            vec!['#', '*']
+        );
+
+        assert!(SymbolSet::parse("a").is_err());
+        assert!(SymbolSet::parse("").is_err());
+    }
+
+    #[test]
+    fn too_big() {
+        let with = |neighbours: &str| {
+            Params::with_overrides(PARAMS_B, &[("neighbours".into(), neighbours.into())])
+        };
+
+        // a lone symbol touches no numbers, and the product of no numbers shouldn't count
+        assert_eq!(
+            b_with_params("1..\n..*", &with("0")),
+            Err("A gear should touch at least one number".to_string())
+        );
+
+        // 20 digits is past u64::MAX
+        let long = format!("{}*", "9".repeat(20));
+        assert_eq!(
+            a_with_params(&long, &Params::defaults(PARAMS_A)),
+            Err("The number at row 0, column 0 is too big".to_string())
+        );
+
+        let ratio = "9999999999*9999999999";
+        assert_eq!(
+            b_with_params(ratio, &with("2")),
+            Err("The gear ratio at row 0, column 10 is too big".to_string())
+        );
+
+        let sum = format!("{}*\n{}*", u64::MAX, u64::MAX);
+        assert_eq!(
+            a_with_params(&sum, &Params::defaults(PARAMS_A)),
+            Err("The part numbers add up to more than fits in a u64".to_string())
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let graph = SchematicGraph::parse("12.\n..\"\n7*.").unwrap();
+        assert_eq!(
+            graph.to_dot(),
+            r#"graph schematic {
+    n0 [shape=box, label="12\n(row 0, cols 0-1)"];
+    n1 [shape=box, label="7\n(row 2, cols 0-0)"];
+    s0 [shape=circle, label="\"\n(row 1, col 2)"];
+    s1 [shape=circle, label="*\n(row 2, col 1)"];
+    s0 -- n0;
+    s1 -- n1;
+}"#
+        );
+    }
+
+    #[test]
+    fn render_plain() {
+        let rendered = render_with_input(SAMPLE_A).render(false);
+
+        assert!(rendered.starts_with(
+            "467..xxx..
+...*......
+..35..633.
+......#...
+617*......
+.....+.xx.
+..592.....
+......755.
+...$.*....
+.664.598..
+"
+        ));
+    }
 }
@@ -1,10 +1,15 @@
-use ahash::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
 use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space1};
-use nom::combinator::{eof, map};
-use nom::multi::separated_list1;
+use nom::character::complete::space1;
+use nom::combinator::map_res;
 use nom::sequence::tuple;
-use nom::IResult;
+
+use crate::helpers::parsing::{parse_all, spaced, unsigned, PResult};
+use crate::helpers::wide::Wide;
+use crate::params::{Param, Params};
+use crate::solutions::SharedParse;
 
 const INPUT_FILE: &str = "input/04.txt";
 
@@ -13,16 +18,94 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> u64 {
-    let cards: Vec<GameCard> = input.lines().map(parse_line).collect();
+pub const PARAMS_A: &[Param] = &[Param {
+    key: "scoring",
+    default: "doubling",
+    description:
+        "points per card: 'doubling', 'linear', or 'table:' then the points for 1, 2, ... wins",
+}];
+
+pub fn a_with_input(input: &str) -> Wide {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
+}
+
+pub fn a_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    let scoring: Scoring = params.get::<String>("scoring")?.parse()?;
+    total_score(&parse_cards(input)?, &scoring)
+}
 
-    let mut out = 0;
+fn total_score(cards: &[GameCard], scoring: &Scoring) -> Result<Wide, String> {
+    let mut out = Wide::default();
 
     for card in cards {
-        out += card.score();
+        out += scoring.score(card.num_wins())?;
     }
 
-    out
+    Ok(out)
+}
+
+/// How many points a card is worth, given how many numbers it matched
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Scoring {
+    /// One point for the first match, then doubled for each one after; the puzzle's rule
+    Doubling,
+    /// A point per match
+    Linear,
+    /// Points for 1, 2, 3, ... matches; more matches than the table covers is an error
+    Table(Vec<Wide>),
+}
+
+impl Scoring {
+    /// No matches is always worth nothing. Points are `Wide`, so even the doubling rule can't
+    /// overflow; the only failure is running off the end of a table.
+    pub fn score(&self, num_wins: u32) -> Result<Wide, String> {
+        if num_wins == 0 {
+            return Ok(Wide::default());
+        }
+
+        match self {
+            Scoring::Doubling => Ok(Wide::pow2(num_wins - 1)),
+            Scoring::Linear => Ok(Wide::from(num_wins)),
+            Scoring::Table(points) => points.get(num_wins as usize - 1).cloned().ok_or_else(|| {
+                format!(
+                    "The scoring table only goes up to {} matches, but a card has {}",
+                    points.len(),
+                    num_wins
+                )
+            }),
+        }
+    }
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            _ => {
+                let table = s.strip_prefix("table:").ok_or_else(|| {
+                    format!(
+                        "Scoring should be 'doubling', 'linear' or 'table:...', not '{}'",
+                        s
+                    )
+                })?;
+
+                table
+                    .split(',')
+                    .map(|points| {
+                        points
+                            .trim()
+                            .parse::<u128>()
+                            .map(Wide::from)
+                            .map_err(|_| format!("Cannot parse '{}' as points", points))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Scoring::Table)
+            }
+        }
+    }
 }
 
 pub fn b() -> String {
@@ -30,48 +113,201 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    let cards: Vec<GameCard> = input.lines().map(parse_line).collect();
+pub const PARAMS_B: &[Param] = &[Param {
+    key: "overflow",
+    default: "error",
+    description: "what to do when a card wins copies past the last card: 'clamp' or 'error'",
+}];
+
+pub fn b_with_input(input: &str) -> Wide {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Cards should not win past the end")
+}
+
+pub fn b_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    total_copies(&parse_cards(input)?, params.get("overflow")?)
+}
+
+/// How part b's copies came about, card by card
+pub fn trace_b(input: &str, params: &Params) -> Result<Cascade, String> {
+    cascade(&parse_cards(input)?, params.get("overflow")?)
+}
+
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Vec<GameCard>;
+
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+    const PARAMS_B: &'static [Param] = PARAMS_B;
+
+    fn parse(input: &str) -> Result<Vec<GameCard>, String> {
+        parse_cards(input)
+    }
+
+    fn a(cards: &Vec<GameCard>, params: &Params) -> Result<String, String> {
+        let scoring: Scoring = params.get::<String>("scoring")?.parse()?;
+        total_score(cards, &scoring).map(|n| n.to_string())
+    }
+
+    fn b(cards: &Vec<GameCard>, params: &Params) -> Result<String, String> {
+        total_copies(cards, params.get("overflow")?).map(|n| n.to_string())
+    }
+}
+
+/// What happens when a card wins copies of cards past the end of the table
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Overflow {
+    /// Win copies of the cards up to the end, and nothing past it
+    Clamp,
+    Error,
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(Overflow::Clamp),
+            "error" => Ok(Overflow::Error),
+            other => Err(format!(
+                "Overflow policy should be 'clamp' or 'error', not '{}'",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CardTrace {
+    pub id: u64,
+    /// Including the original
+    pub copies: Wide,
+    /// (id, copies) for each earlier card which won copies of this one
+    pub contributors: Vec<(u64, Wide)>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Cascade {
+    pub cards: Vec<CardTrace>,
+}
+
+impl Cascade {
+    pub fn total(&self) -> Wide {
+        self.cards.iter().map(|c| &c.copies).sum()
+    }
+}
+
+impl Display for Cascade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for card in self.cards.iter() {
+            write!(f, "Card {}: {} copies", card.id, card.copies)?;
+
+            if !card.contributors.is_empty() {
+                let contributors: Vec<String> = card
+                    .contributors
+                    .iter()
+                    .map(|(id, copies)| format!("{} from card {}", copies, id))
+                    .collect();
+                write!(f, " (1 original, {})", contributors.join(", "))?;
+            }
+
+            writeln!(f)?;
+        }
+
+        write!(f, "Total: {}", self.total())
+    }
+}
+
+/// How many of the cards after card `i` it wins copies of
+fn reach(cards: &[GameCard], i: usize, overflow: Overflow) -> Result<usize, String> {
+    let num_wins = cards[i].num_wins() as usize;
+    let remaining = cards.len() - 1 - i;
 
-    let mut out = 0;
+    if num_wins <= remaining {
+        return Ok(num_wins);
+    }
 
-    let mut value_cache = HashMap::default();
+    match overflow {
+        Overflow::Clamp => Ok(remaining),
+        Overflow::Error => Err(format!(
+            "Card {} wins copies of the next {} cards, but only {} come after it",
+            cards[i].id, num_wins, remaining
+        )),
+    }
+}
 
-    for i in (0..cards.len()).rev() {
-        let card = &cards[i];
-        let num_wins = card.num_wins() as usize;
+/// The total number of cards, without keeping track of where they came from. A card's copies go
+/// to a run of the cards after it, so they're added to a running count where the run starts and
+/// taken off again where it ends; that's a couple of additions per card, however much it wins.
+fn total_copies(cards: &[GameCard], overflow: Overflow) -> Result<Wide, String> {
+    let mut run_ends: Vec<Wide> = vec![Wide::default(); cards.len() + 1];
+    let mut won = Wide::default();
+    let mut total = Wide::default();
 
-        // you always keep the card itself; plus anything you win (transitively)
-        let mut card_value = 1;
+    for i in 0..cards.len() {
+        won = std::mem::take(&mut won) - std::mem::take(&mut run_ends[i]);
+        let copies = won.clone() + Wide::from(1);
 
-        for j in (i + 1)..=(i + num_wins) {
-            card_value += value_cache
-                .get(&j)
-                .expect("Iteration order should guarantee the value cache is populated");
+        let reach = reach(cards, i, overflow)?;
+        if reach > 0 {
+            won += copies.clone();
+            run_ends[i + 1 + reach] += copies.clone();
         }
 
-        value_cache.insert(i, card_value);
-        out += card_value;
+        total += copies;
     }
 
-    out
+    Ok(total)
 }
 
-fn parse_line(input: &str) -> GameCard {
-    fn parse_helper(input: &str) -> IResult<&str, GameCard> {
-        let mut parse_num = map(digit1, |d: &str| d.parse::<u64>().unwrap());
+fn cascade(cards: &[GameCard], overflow: Overflow) -> Result<Cascade, String> {
+    // each card can double the number of copies of everything after it, so these get big fast
+    let mut traces: Vec<CardTrace> = cards
+        .iter()
+        .map(|card| CardTrace {
+            id: card.id,
+            copies: Wide::from(1),
+            contributors: Vec::new(),
+        })
+        .collect();
+
+    // by the time a card is reached, every card before it has handed over its copies
+    for i in 0..cards.len() {
+        let reach = reach(cards, i, overflow)?;
+        let copies = traces[i].copies.clone();
+        let id = traces[i].id;
+
+        for trace in traces[i + 1..=i + reach].iter_mut() {
+            trace.copies += copies.clone();
+            trace.contributors.push((id, copies.clone()));
+        }
+    }
+
+    Ok(Cascade { cards: traces })
+}
+
+fn parse_cards(input: &str) -> Result<Vec<GameCard>, String> {
+    input.lines().map(parse_line).collect()
+}
+
+/// Fails if the line isn't a card, or if any of its numbers is 100 or more
+fn parse_line(input: &str) -> Result<GameCard, String> {
+    fn parse_helper(input: &str) -> PResult<'_, GameCard> {
+        let numbers = || {
+            map_res(spaced(unsigned), |nums: Vec<u8>| {
+                NumberSet::from_numbers(&nums)
+            })
+        };
 
         let (input, _) = tuple((tag("Card"), space1))(input)?;
-        let (input, id) = parse_num(input)?;
+        let (input, id) = unsigned(input)?;
         let (input, _) = tuple((tag(":"), space1))(input)?;
-        let (input, winning_numbers) = separated_list1(space1, &mut parse_num)(input)?;
+        let (input, winning_numbers) = numbers()(input)?;
         let (input, _) = tuple((space1, tag("|"), space1))(input)?;
-        let (input, actual_numbers) = separated_list1(space1, &mut parse_num)(input)?;
-
-        let (_, _) = eof(input)?;
+        let (input, actual_numbers) = numbers()(input)?;
 
         Ok((
-            "",
+            input,
             GameCard {
                 id,
                 winning_numbers,
@@ -80,38 +316,44 @@ fn parse_line(input: &str) -> GameCard {
         ))
     }
 
-    let (_, out) = parse_helper(input).unwrap();
-    out
+    parse_all(input, parse_helper).map_err(|e| format!("Cannot parse card '{}': {}", input, e))
 }
 
-#[derive(Clone, Eq, PartialEq, Debug)]
-struct GameCard {
-    id: u64,
-    winning_numbers: Vec<u64>,
-    actual_numbers: Vec<u64>,
-}
+/// A set of card numbers, which are all below 100, as a bitset
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+struct NumberSet(u128);
 
-impl GameCard {
-    fn num_wins(&self) -> u64 {
-        let mut num_wins: u64 = 0;
-        for a in self.actual_numbers.iter() {
-            if self.winning_numbers.contains(a) {
-                num_wins += 1;
+impl NumberSet {
+    const LIMIT: u8 = 100;
+
+    /// Errors with the first number which is too big to fit
+    fn from_numbers(numbers: &[u8]) -> Result<Self, u8> {
+        let mut bits = 0;
+        for &n in numbers {
+            if n >= Self::LIMIT {
+                return Err(n);
             }
+            bits |= 1 << n;
         }
-        num_wins
+        Ok(NumberSet(bits))
     }
 
-    fn score(&self) -> u64 {
-        get_score(self.num_wins())
+    fn count_common(self, other: NumberSet) -> u32 {
+        (self.0 & other.0).count_ones()
     }
 }
 
-fn get_score(num_wins: u64) -> u64 {
-    if num_wins == 0 {
-        0
-    } else {
-        1 << (num_wins - 1)
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct GameCard {
+    id: u64,
+    winning_numbers: NumberSet,
+    actual_numbers: NumberSet,
+}
+
+impl GameCard {
+    /// Numbers repeated on the same side of the card only count once
+    fn num_wins(&self) -> u32 {
+        self.winning_numbers.count_common(self.actual_numbers)
     }
 }
 
@@ -128,11 +370,147 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
     #[test]
     fn sample_a() {
-        assert_eq!(a_with_input(SAMPLE_A), 13);
+        assert_eq!(a_with_input(SAMPLE_A), Wide::from(13));
     }
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_A), 30);
+        assert_eq!(b_with_input(SAMPLE_A), Wide::from(30));
+    }
+
+    #[test]
+    fn huge_cascade() {
+        // a card can match at most 100 numbers, so each card wins copies of (up to) the next 100
+        // cards, and those windows overlap into one long chain. Card i ends up with one copy plus
+        // all the copies of the (up to) 100 cards before it, which nearly doubles every card, so
+        // 130 of them go well past 128 bits.
+        let n = 130;
+        let wins = |i: usize| (n - 1 - i).min(100);
+        let input = (0..n)
+            .map(|i| {
+                if wins(i) == 0 {
+                    return format!("Card {}: 0 | 1", i + 1);
+                }
+                let numbers = (0..wins(i)).map(|w| w.to_string()).collect::<Vec<_>>();
+                format!(
+                    "Card {}: {} | {}",
+                    i + 1,
+                    numbers.join(" "),
+                    numbers.join(" ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // the first 30 cards match 100 numbers, and the rest count down from 99 to 0
+        assert_eq!(
+            a_with_input(&input),
+            Wide::from(31) * Wide::pow2(99) - Wide::from(1)
+        );
+
+        // scoring the 129 cards with any matches as the biggest u128 each overflows too
+        let table = format!("table:{}", vec![u128::MAX.to_string(); 100].join(","));
+        let params = Params::with_overrides(PARAMS_A, &[("scoring".to_string(), table)]);
+        let score = a_with_params(&input, &params).unwrap();
+        assert_eq!(score, Wide::from(129) * Wide::from(u128::MAX));
+        assert!(score > Wide::pow2(128));
+
+        let mut copies: Vec<Wide> = Vec::new();
+        for i in 0..n {
+            let from_earlier: Wide = copies[i.saturating_sub(100)..i].iter().sum();
+            copies.push(from_earlier + Wide::from(1));
+        }
+        let total: Wide = copies.iter().sum();
+        assert!(total > Wide::pow2(128));
+        assert_eq!(b_with_input(&input), total);
+    }
+
+    #[test]
+    fn bitset_matching() {
+        let card = parse_line("Card 9: 0 99 5 5 | 5 99 98 0 5").unwrap();
+        assert_eq!(card.num_wins(), 3);
+
+        let too_big = "Card 1: 41 | 41\nCard 2: 100 | 1";
+        let err = a_with_params(too_big, &Params::defaults(PARAMS_A)).unwrap_err();
+        assert!(
+            err.starts_with("Cannot parse card 'Card 2: 100 | 1'"),
+            "{}",
+            err
+        );
+        assert!(b_with_params(too_big, &Params::defaults(PARAMS_B)).is_err());
+        assert!(trace_b(too_big, &Params::defaults(PARAMS_B)).is_err());
+    }
+
+    #[test]
+    fn scoring_variants() {
+        let scoring = |s: &str| s.parse::<Scoring>().unwrap();
+
+        assert_eq!(scoring("doubling").score(4), Ok(Wide::from(8)));
+        assert_eq!(scoring("doubling").score(100), Ok(Wide::pow2(99)));
+        assert_eq!(scoring("linear").score(4), Ok(Wide::from(4)));
+        assert_eq!(scoring("linear").score(0), Ok(Wide::from(0)));
+
+        let table = scoring("table:1, 3, 6");
+        assert_eq!(table.score(3), Ok(Wide::from(6)));
+        assert!(table.score(4).is_err());
+
+        assert!("table:1,x".parse::<Scoring>().is_err());
+        assert!("tripling".parse::<Scoring>().is_err());
+
+        let linear =
+            Params::with_overrides(PARAMS_A, &[("scoring".to_string(), "linear".to_string())]);
+        // matches are 4, 2, 2, 1, 0, 0
+        assert_eq!(a_with_params(SAMPLE_A, &linear), Ok(Wide::from(9)));
+    }
+
+    #[test]
+    fn overflow_policy() {
+        // card 2 wins one copy of a card that isn't there
+        let input = "Card 1: 1 | 1
+Card 2: 5 | 5";
+
+        let set = |policy: &str| {
+            Params::with_overrides(PARAMS_B, &[("overflow".to_string(), policy.to_string())])
+        };
+
+        assert_eq!(
+            b_with_params(input, &set("error")),
+            Err("Card 2 wins copies of the next 1 cards, but only 0 come after it".to_string())
+        );
+        assert_eq!(b_with_params(input, &set("clamp")), Ok(Wide::from(3)));
+        assert!(b_with_params(input, &set("wrap")).is_err());
+    }
+
+    #[test]
+    fn trace() {
+        let cascade = trace_b(SAMPLE_A, &Params::defaults(PARAMS_B)).unwrap();
+
+        assert_eq!(
+            cascade.cards[3],
+            CardTrace {
+                id: 4,
+                copies: Wide::from(8),
+                contributors: vec![(1, Wide::from(1)), (2, Wide::from(2)), (3, Wide::from(4))],
+            }
+        );
+
+        assert_eq!(
+            cascade.to_string(),
+            "Card 1: 1 copies
+Card 2: 2 copies (1 original, 1 from card 1)
+Card 3: 4 copies (1 original, 1 from card 1, 2 from card 2)
+Card 4: 8 copies (1 original, 1 from card 1, 2 from card 2, 4 from card 3)
+Card 5: 14 copies (1 original, 1 from card 1, 4 from card 3, 8 from card 4)
+Card 6: 1 copies
+Total: 30"
+        );
+
+        // the plain count has to agree with the trace
+        let clamp = Params::with_overrides(PARAMS_B, &[("overflow".into(), "clamp".into())]);
+        let input = format!("{}\nCard 7: 1 2 3 | 1 2 3\nCard 8: 5 | 5", SAMPLE_A);
+        assert_eq!(
+            b_with_params(&input, &clamp),
+            Ok(trace_b(&input, &clamp).unwrap().total())
+        );
     }
 }
@@ -1,20 +1,39 @@
+use ahash::HashMap;
+use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, newline, space0, space1};
-use nom::combinator::{eof, map};
-use nom::multi::separated_list1;
-use nom::sequence::tuple;
-use nom::IResult;
+use nom::character::complete::{alpha1, line_ending, space0, space1};
+use nom::multi::many0;
+use nom::sequence::{preceded, tuple};
+
+use crate::helpers::intervals::{Interval, IntervalSet};
+use crate::helpers::parsing::{blank_line, labelled, lines, parse_all, spaced, unsigned, PResult};
+use crate::helpers::piecewise::{OverlapError, PiecewiseMap, Shift};
+use crate::helpers::search::{bfs, Paths};
+use crate::params::{Param, Params};
+use crate::solutions::SharedParse;
 
 const INPUT_FILE: &str = "input/05.txt";
 
+pub const PARAMS_A: &[Param] = &[Param {
+    key: "to",
+    default: "location",
+    description: "category to resolve the seeds to",
+}];
+
+pub const PARAMS_B: &[Param] = PARAMS_A;
+
 pub fn a() -> String {
     let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    let almanac = parse(input);
-    almanac.least_location_a()
+pub fn a_with_input(input: &str) -> i64 {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
+}
+
+pub fn a_with_params(input: &str, params: &Params) -> Result<i64, String> {
+    let almanac = parse(input)?;
+    almanac.least_location_a(&params.get::<String>("to")?)
 }
 
 pub fn b() -> String {
@@ -22,328 +41,228 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    let almanac = parse(input);
-    almanac.least_location_b()
+pub fn b_with_input(input: &str) -> i64 {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
+}
+
+pub fn b_with_params(input: &str, params: &Params) -> Result<i64, String> {
+    let almanac = parse(input)?;
+    almanac.least_location_b(&params.get::<String>("to")?)
 }
 
-fn parse(input: &str) -> Almanac {
-    fn parse_num(input: &str) -> IResult<&str, usize> {
-        map(digit1, |d: &str| d.parse::<usize>().unwrap())(input)
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Almanac;
+
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+    const PARAMS_B: &'static [Param] = PARAMS_B;
+
+    fn parse(input: &str) -> Result<Almanac, String> {
+        parse(input)
     }
 
-    fn parse_seeds(input: &str) -> IResult<&str, Vec<usize>> {
-        let (input, _) = tuple((tag("seeds:"), space1))(input)?;
-        let (input, seeds) = separated_list1(space1, parse_num)(input)?;
-        Ok((input, seeds))
+    fn a(almanac: &Almanac, params: &Params) -> Result<String, String> {
+        almanac
+            .least_location_a(&params.get::<String>("to")?)
+            .map(|n| n.to_string())
     }
 
-    fn parse_mapping(input: &str) -> IResult<&str, Mapping> {
-        let (input, target_start) = parse_num(input)?;
+    fn b(almanac: &Almanac, params: &Params) -> Result<String, String> {
+        almanac
+            .least_location_b(&params.get::<String>("to")?)
+            .map(|n| n.to_string())
+    }
+}
+
+/// Fails if the almanac doesn't parse, has a map whose source ranges overlap, or has more than one
+/// map between the same two categories
+fn parse(input: &str) -> Result<Almanac, String> {
+    fn parse_shift(input: &str) -> PResult<'_, Shift> {
+        let (input, target_start) = unsigned::<i64>(input)?;
         let (input, _) = space1(input)?;
-        let (input, source_start) = parse_num(input)?;
+        let (input, source_start) = unsigned(input)?;
         let (input, _) = space1(input)?;
-        let (input, length) = parse_num(input)?;
+        let (input, length) = unsigned::<i64>(input)?;
 
         Ok((
             input,
-            Mapping {
-                source_start,
-                target_start,
-                source_end: source_start + length,
+            Shift {
+                domain: Interval::new(source_start, source_start + length),
+                offset: target_start - source_start,
             },
         ))
     }
 
-    fn blank_then_map<'a>(input: &'a str, map_name: &str) -> IResult<&'a str, FullMapping> {
-        // two newlines before it starts
-        let (input, _) = newline(input)?;
-        let (input, _) = newline(input)?;
-
-        let (input, _) = tuple((tag(map_name), tag(":"), space0, newline))(input)?;
-
-        let (input, mut mappings) = separated_list1(newline, parse_mapping)(input)?;
+    /// Something like "seed-to-soil map:" and its lines, as (source, target, shifts)
+    fn section(input: &str) -> PResult<'_, (&str, &str, Vec<Shift>)> {
+        let (input, (source, _, target, _, _, _)) = tuple((
+            alpha1,
+            tag("-to-"),
+            alpha1,
+            tag(" map:"),
+            space0,
+            line_ending,
+        ))(input)?;
 
-        mappings.sort();
+        let (input, shifts) = lines(parse_shift)(input)?;
 
-        for i in 1..mappings.len() {
-            if mappings[i - 1].intersects(&mappings[i]) {
-                panic!(
-                    "OVERLAPPING INTERVALS OH NOOO {:?} and {:?}",
-                    mappings[i - 1],
-                    mappings[i]
-                );
-            }
-        }
-
-        Ok((input, FullMapping { mappings }))
+        Ok((input, (source, target, shifts)))
     }
 
-    fn full_parse(input: &str) -> IResult<&str, Almanac> {
-        let (input, seeds) = parse_seeds(input)?;
+    type Sections<'a> = Vec<(&'a str, &'a str, Vec<Shift>)>;
 
-        let (input, seed_to_soil) = blank_then_map(input, "seed-to-soil map")?;
-        let (input, soil_to_fertilizer) = blank_then_map(input, "soil-to-fertilizer map")?;
-        let (input, fertilizer_to_water) = blank_then_map(input, "fertilizer-to-water map")?;
-        let (input, water_to_light) = blank_then_map(input, "water-to-light map")?;
-        let (input, light_to_temperature) = blank_then_map(input, "light-to-temperature map")?;
-        let (input, temperature_to_humidity) =
-            blank_then_map(input, "temperature-to-humidity map")?;
-        let (input, humidity_to_location) = blank_then_map(input, "humidity-to-location map")?;
+    fn full_parse(input: &str) -> PResult<'_, (Vec<i64>, Sections<'_>)> {
+        let (input, seeds) = labelled("seeds", spaced(unsigned))(input)?;
 
-        let (_, _) = eof(input)?;
+        let (input, sections) = many0(preceded(blank_line, section))(input)?;
 
-        Ok((
-            "",
-            Almanac {
-                seeds,
-                mappings: vec![
-                    seed_to_soil,
-                    soil_to_fertilizer,
-                    fertilizer_to_water,
-                    water_to_light,
-                    light_to_temperature,
-                    temperature_to_humidity,
-                    humidity_to_location,
-                ],
-            },
-        ))
+        Ok((input, (seeds, sections)))
     }
 
-    let (_, almanac) = full_parse(input).expect("Input should parse");
-    almanac
-}
+    let (seeds, sections) =
+        parse_all(input, full_parse).map_err(|e| format!("Cannot parse the almanac: {}", e))?;
+
+    let mut categories = CategoryGraph::default();
+    for (source, target, shifts) in sections {
+        let map = PiecewiseMap::from_shifts(shifts).map_err(|OverlapError(a, b)| {
+            format!(
+                "The {}-to-{} map has overlapping source ranges {}..{} and {}..{}",
+                source, target, a.start, a.end, b.start, b.end
+            )
+        })?;
+        categories.add(source, target, map)?;
+    }
 
-struct Almanac {
-    seeds: Vec<usize>,
-    mappings: Vec<FullMapping>,
+    Ok(Almanac { seeds, categories })
 }
 
-struct FullMapping {
-    mappings: Vec<Mapping>,
+/// Every "X-to-Y map" in the almanac, as edges from category X to category Y
+#[derive(Default)]
+pub struct CategoryGraph {
+    maps: HashMap<(String, String), PiecewiseMap>,
+    // the categories each one maps to and from directly, in the order the sections came in
+    targets: HashMap<String, Vec<String>>,
+    sources: HashMap<String, Vec<String>>,
 }
 
-impl FullMapping {
-    fn resolve(&self, input: usize) -> usize {
-        for m in &self.mappings {
-            if let Some(out) = m.resolve(input) {
-                return out;
-            }
+impl CategoryGraph {
+    /// Errors if there's already a map from `source` to `target`
+    fn add(&mut self, source: &str, target: &str, map: PiecewiseMap) -> Result<(), String> {
+        let key = (source.to_string(), target.to_string());
+        if self.maps.contains_key(&key) {
+            return Err(format!(
+                "There should only be one {}-to-{} map",
+                source, target
+            ));
         }
-
-        // anything that doesn't map stays the same
-        input
+        self.maps.insert(key, map);
+
+        self.targets
+            .entry(source.to_string())
+            .or_default()
+            .push(target.to_string());
+        self.sources
+            .entry(target.to_string())
+            .or_default()
+            .push(source.to_string());
+
+        Ok(())
     }
 
-    // PRE: self.mappings are sorted (ascending)
-    fn resolve_interval(&self, input: Interval) -> Vec<Interval> {
-        for i in 1..self.mappings.len() {
-            if !(self.mappings[i - 1] < self.mappings[i]) {
-                panic!("Mappings must be sorted!");
-            }
-        }
-
-        let mut start = input.start;
-        let end = input.end;
-
-        if start >= end {
-            return vec![];
-        }
+    pub fn get(&self, source: &str, target: &str) -> Option<&PiecewiseMap> {
+        self.maps.get(&(source.to_string(), target.to_string()))
+    }
 
-        let mut out = Vec::new();
-
-        for mapping in &self.mappings {
-            // couldn't decide if a zero-length mapping actually cause a problem but let's just skip it
-            if mapping.source_start == mapping.source_end {
-                continue;
-            }
-
-            // basically there are six possible cases:
-            //      1.  input starts and ends before mapping
-            //      2.  input starts before mapping, but has some overlap; ends before mapping does
-            //      3.  input starts before mapping, and continues after it
-            //      4.  input starts and ends inside the mapping
-            //      5.  input starts within the mapping, but ends after it
-            //      6.  input starts and ends after the mapping
-
-            // case 1: in this case, we don't touch this mapping or any subsequent mapping; done
-            //      leftover interval bits are collected at the end
-            if end <= mapping.source_start {
-                break;
-            }
-
-            // now we know that end > mapping.source_start
-
-            // if there is any portion of the interval before the mapping, preserve it and skip
-            // ahead to the bit
-            if start < mapping.source_start {
-                out.push(Interval {
-                    start,
-                    end: mapping.source_start,
-                });
-                start = mapping.source_start;
-            }
-
-            // now we know that start >= mapping.source_start
-            // if additionally start is before mapping.source_end, then some amount overlaps
-            if start < mapping.source_end {
-                let inside_end = if end < mapping.source_end {
-                    end
-                } else {
-                    mapping.source_end
-                };
-
-                let mapped_start = start - mapping.source_start + mapping.target_start;
-                let mapped_end = inside_end - mapping.source_start + mapping.target_start;
-
-                if mapped_start < mapped_end {
-                    out.push(Interval {
-                        start: mapped_start,
-                        end: mapped_end,
-                    });
-                }
-
-                // this may make start > end, which ends the resolution
-                start = mapping.source_end;
-                if start >= end {
-                    return out;
-                }
-            }
+    /// A single map taking `from` numbers to `to` numbers, composed along the shortest chain of
+    /// maps between the two; the error names the gap if no chain gets there
+    pub fn resolve(&self, from: &str, to: &str) -> Result<PiecewiseMap, String> {
+        fn step<'a>(edges: &'a HashMap<String, Vec<String>>, node: &str) -> Vec<&'a str> {
+            edges
+                .get(node)
+                .map(|next| next.iter().map(String::as_str).collect())
+                .unwrap_or_default()
         }
 
-        // any leftover interval space didn't match anything so we can just keep what we have
-        if start < end {
-            out.push(Interval { start, end });
-        }
+        let forward = bfs([from], |node| step(&self.targets, node), Paths::Track);
+
+        let Some(path) = forward.path_to(&to) else {
+            // everything the start gets to, nearest first, and everything which gets to the end,
+            // nearest last; the missing map goes from somewhere in the first to the second
+            let backward = bfs([to], |node| step(&self.sources, node), Paths::Skip);
+
+            let mut reached: Vec<(usize, &str)> =
+                forward.costs.iter().map(|(&n, &c)| (c, n)).collect();
+            reached.sort_unstable();
+            let mut leads_on: Vec<(usize, &str)> =
+                backward.costs.iter().map(|(&n, &c)| (c, n)).collect();
+            leads_on.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+
+            let names = |found: &[(usize, &str)]| found.iter().map(|(_, n)| *n).join(", ");
+
+            // both lists hold at least their own end, so there's always a last and a first
+            return Err(format!(
+                "No path from '{}' to '{}': nothing maps any of [{}] to any of [{}] \
+                 (a missing {}-to-{} map?)",
+                from,
+                to,
+                names(&reached),
+                names(&leads_on),
+                reached[reached.len() - 1].1,
+                leads_on[0].1,
+            ));
+        };
 
-        unify_intervals(out)
+        Ok(path.windows(2).fold(PiecewiseMap::identity(), |acc, link| {
+            acc.then(self.get(link[0], link[1]).expect("Path should follow maps"))
+        }))
     }
 }
 
-fn unify_intervals(mut intervals: Vec<Interval>) -> Vec<Interval> {
-    if intervals.is_empty() {
-        return intervals;
-    }
-
-    intervals.sort();
-
-    let mut iter = intervals.into_iter();
-
-    let mut running = iter.next().unwrap();
-
-    let mut out = Vec::new();
-
-    for next in iter {
-        assert!(running.start <= next.start);
-
-        if running.end < next.start {
-            out.push(running);
-            running = next;
-        } else {
-            running = Interval {
-                start: running.start,
-                end: running.end.max(next.end),
-            }
-        }
-    }
-
-    out.push(running);
-
-    out
+pub struct Almanac {
+    seeds: Vec<i64>,
+    pub categories: CategoryGraph,
 }
 
 impl Almanac {
-    fn resolve_seed(&self, seed: usize) -> usize {
-        let mut running = seed;
-
-        for mapping in &self.mappings {
-            running = mapping.resolve(running);
-        }
-
-        running
+    /// The whole chain from seeds to the target category as a single map
+    fn seed_to(&self, target: &str) -> Result<PiecewiseMap, String> {
+        self.categories.resolve("seed", target)
     }
 
-    fn least_location_a(&self) -> usize {
+    fn least_location_a(&self, target: &str) -> Result<i64, String> {
+        let full = self.seed_to(target)?;
+
         self.seeds
             .iter()
-            .copied()
-            .map(|seed| self.resolve_seed(seed))
+            .map(|&seed| full.apply(seed))
             .min()
-            .expect("Seeds should be nonempty")
+            .ok_or_else(|| "There should be at least one seed".to_string())
     }
 
-    fn least_location_b(&self) -> usize {
-        assert_eq!(
-            self.seeds.len() % 2,
-            0,
-            "Should have an even number of seeds"
-        );
+    fn least_location_b(&self, target: &str) -> Result<i64, String> {
+        if !self.seeds.len().is_multiple_of(2) {
+            return Err(format!(
+                "Seeds should come in (start, length) pairs, but there are {} numbers",
+                self.seeds.len()
+            ));
+        }
 
-        let seed_intervals: Vec<Interval> = self
+        let full = self.seed_to(target)?;
+
+        let locations: IntervalSet<i64> = self
             .seeds
             .chunks_exact(2)
-            .map(|chunk| {
-                if let [start, len] = chunk {
-                    Interval {
-                        start: *start,
-                        end: start + len,
-                    }
-                } else {
-                    panic!();
-                }
+            .flat_map(|chunk| {
+                full.apply_interval(Interval::new(chunk[0], chunk[0] + chunk[1]))
+                    .iter()
+                    .collect::<Vec<_>>()
             })
             .collect();
 
-        let mut total_min = usize::MAX;
-
-        for interval in seed_intervals.iter().copied() {
-            let mut running = vec![interval];
-            for m in &self.mappings {
-                let mut next_running = vec![];
-
-                for interval in running {
-                    next_running.extend(m.resolve_interval(interval));
-                }
-
-                running = unify_intervals(next_running);
-            }
-
-            let my_min = running[0].start;
-            total_min = total_min.min(my_min);
-        }
-
-        total_min
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
-struct Interval {
-    // inclusive
-    start: usize,
-    // exclusive
-    end: usize,
-}
-
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
-struct Mapping {
-    // inclusive
-    source_start: usize,
-    // exclusive
-    source_end: usize,
-    target_start: usize,
-}
-
-impl Mapping {
-    fn resolve(&self, input: usize) -> Option<usize> {
-        if input < self.source_start || input >= self.source_end {
-            None
-        } else {
-            Some(input - self.source_start + self.target_start)
-        }
-    }
-
-    fn intersects(&self, other: &Self) -> bool {
-        self.source_start < other.source_end && other.source_start < self.source_end
+        locations
+            .min()
+            .ok_or_else(|| "The seed ranges should not all be empty".to_string())
     }
 }
 
@@ -351,7 +270,7 @@ impl Mapping {
 mod tests {
     use super::*;
 
-    const SAMPLE_INPUT: &'static str = "seeds: 79 14 55 13
+    const SAMPLE_INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -387,12 +306,13 @@ humidity-to-location map:
 
     #[test]
     fn examples_a() {
-        let almanac = parse(SAMPLE_INPUT);
+        let almanac = parse(SAMPLE_INPUT).unwrap();
+        let seed_to_soil = almanac.categories.get("seed", "soil").unwrap();
 
-        assert_eq!(almanac.mappings[0].resolve(79), 81);
-        assert_eq!(almanac.mappings[0].resolve(14), 14);
-        assert_eq!(almanac.mappings[0].resolve(55), 57);
-        assert_eq!(almanac.mappings[0].resolve(13), 13);
+        assert_eq!(seed_to_soil.apply(79), 81);
+        assert_eq!(seed_to_soil.apply(14), 14);
+        assert_eq!(seed_to_soil.apply(55), 57);
+        assert_eq!(seed_to_soil.apply(13), 13);
     }
 
     #[test]
@@ -402,69 +322,161 @@ humidity-to-location map:
 
     #[test]
     fn examples_b() {
-        let mut mapping = FullMapping {
-            mappings: vec![
-                Mapping {
-                    source_start: 98,
-                    source_end: 98 + 2,
-                    target_start: 50,
-                },
-                Mapping {
-                    source_start: 50,
-                    source_end: 50 + 48,
-                    target_start: 52,
-                },
-            ],
+        let mapping = PiecewiseMap::from_shifts([
+            Shift {
+                domain: Interval::new(98, 98 + 2),
+                offset: 50 - 98,
+            },
+            Shift {
+                domain: Interval::new(50, 50 + 48),
+                offset: 52 - 50,
+            },
+        ])
+        .unwrap();
+
+        let resolve_interval = |start, end| -> Vec<Interval<i64>> {
+            mapping
+                .apply_interval(Interval::new(start, end))
+                .iter()
+                .collect()
         };
 
-        mapping.mappings.sort();
-
-        assert_eq!(mapping.resolve(97), 52 + 47);
-        assert_eq!(mapping.resolve(98), 50);
+        assert_eq!(mapping.apply(97), 52 + 47);
+        assert_eq!(mapping.apply(98), 50);
 
         // 50 to 98 maps directly (with the latter mapping) to 52 to 100
-        assert_eq!(
-            mapping.resolve_interval(Interval { start: 50, end: 98 }),
-            vec![Interval {
-                start: 52,
-                end: 100
-            }]
-        );
+        assert_eq!(resolve_interval(50, 98), vec![Interval::new(52, 100)]);
         // 98 to 100 maps directly (with the former mapping) to 50 to 52
+        assert_eq!(resolve_interval(98, 100), vec![Interval::new(50, 52)]);
+        // 0 to 50 doesn't touch anything, so it stays alone
+        assert_eq!(resolve_interval(0, 50), vec![Interval::new(0, 50)]);
+        // likewise 100 to (whatever) stays alone
+        assert_eq!(resolve_interval(100, 400), vec![Interval::new(100, 400)]);
+
+        // then for some overlap examples
+        // this has everything, so basically it covers the whole space, super easy
+        assert_eq!(resolve_interval(0, 400), vec![Interval::new(0, 400)]);
+    }
+
+    #[test]
+    fn composed_chain() {
+        let almanac = parse(SAMPLE_INPUT).unwrap();
+        let full = almanac.categories.resolve("seed", "location").unwrap();
+
+        // worked examples from the puzzle text
+        assert_eq!(full.apply(79), 82);
+        assert_eq!(full.apply(14), 43);
+        assert_eq!(full.apply(55), 86);
+        assert_eq!(full.apply(13), 35);
+    }
+
+    #[test]
+    fn sample_b() {
+        assert_eq!(b_with_input(SAMPLE_INPUT), 46);
+    }
+
+    #[test]
+    fn sections_in_any_order() {
+        let (seeds, rest) = SAMPLE_INPUT.split_once("\n\n").unwrap();
+        let shuffled = std::iter::once(seeds)
+            .chain(rest.split("\n\n").collect::<Vec<_>>().into_iter().rev())
+            .join("\n\n");
+
+        assert_eq!(a_with_input(&shuffled), 35);
+        assert_eq!(b_with_input(&shuffled), 46);
+    }
+
+    #[test]
+    fn any_reachable_category() {
+        let input = format!("{}\n\nsoil-to-colour map:\n100 0 50", SAMPLE_INPUT);
+        let almanac = parse(&input).unwrap();
+
+        // part way along the chain, or off down a side branch
+        let to_water = almanac.categories.resolve("seed", "water").unwrap();
+        assert_eq!(to_water.apply(79), 81);
         assert_eq!(
-            mapping.resolve_interval(Interval {
-                start: 98,
-                end: 100
-            }),
-            vec![Interval { start: 50, end: 52 }]
+            almanac
+                .categories
+                .resolve("soil", "colour")
+                .unwrap()
+                .apply(14),
+            114
         );
-        // 0 to 50 doesn't touch anything, so it stays alone
         assert_eq!(
-            mapping.resolve_interval(Interval { start: 0, end: 50 }),
-            vec![Interval { start: 0, end: 50 }]
+            almanac
+                .categories
+                .resolve("light", "light")
+                .unwrap()
+                .apply(7),
+            7
         );
-        // likewise 100 to (whatever) stays alone
+
+        let params = Params::with_overrides(PARAMS_A, &[("to".into(), "colour".into())]);
+        assert_eq!(a_with_params(&input, &params), Ok(57));
+    }
+
+    #[test]
+    fn missing_link() {
+        let (before, after) = SAMPLE_INPUT.split_once("water-to-light map:").unwrap();
+        let (_, after) = after.split_once("\n\n").unwrap();
+        let input = format!("{}{}", before, after);
+
         assert_eq!(
-            mapping.resolve_interval(Interval {
-                start: 100,
-                end: 400
-            }),
-            vec![Interval {
-                start: 100,
-                end: 400
-            }]
+            parse(&input)
+                .unwrap()
+                .categories
+                .resolve("seed", "location")
+                .unwrap_err(),
+            "No path from 'seed' to 'location': nothing maps any of [seed, soil, fertilizer, \
+             water] to any of [light, temperature, humidity, location] (a missing water-to-light \
+             map?)"
         );
 
-        // then for some overlap examples
-        // this has everything, so basically it covers the whole space, super easy
+        let almanac = parse(SAMPLE_INPUT).unwrap();
+        assert!(almanac.categories.resolve("location", "seed").is_err());
+        assert!(almanac.categories.resolve("seed", "nowhere").is_err());
+    }
+
+    #[test]
+    fn duplicate_map() {
+        let input = format!("{}\n\nsoil-to-fertilizer map:\n0 0 10", SAMPLE_INPUT);
+        let params = Params::defaults(PARAMS_A);
+
         assert_eq!(
-            mapping.resolve_interval(Interval { start: 0, end: 400 }),
-            vec![Interval { start: 0, end: 400 }]
+            a_with_params(&input, &params),
+            Err("There should only be one soil-to-fertilizer map".to_string())
         );
+        assert!(b_with_params(&input, &params).is_err());
+        assert!(Solver::parse(&input).is_err());
     }
 
     #[test]
-    fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_INPUT), 46);
+    fn bad_almanacs() {
+        let params = Params::defaults(PARAMS_A);
+
+        let overlapping = format!(
+            "{}\n\nlocation-to-colour map:\n0 10 10\n50 15 10",
+            SAMPLE_INPUT
+        );
+        assert_eq!(
+            a_with_params(&overlapping, &params),
+            Err(
+                "The location-to-colour map has overlapping source ranges 10..20 and 15..25"
+                    .to_string()
+            )
+        );
+
+        let odd_seeds = SAMPLE_INPUT.replace("seeds: 79 14 55 13", "seeds: 79 14 55");
+        assert_eq!(a_with_params(&odd_seeds, &params), Ok(43));
+        assert_eq!(
+            b_with_params(&odd_seeds, &params),
+            Err("Seeds should come in (start, length) pairs, but there are 3 numbers".to_string())
+        );
+
+        let empty_ranges = SAMPLE_INPUT.replace("seeds: 79 14 55 13", "seeds: 79 0 55 0");
+        assert_eq!(
+            b_with_params(&empty_ranges, &params),
+            Err("The seed ranges should not all be empty".to_string())
+        );
     }
 }
@@ -1,9 +1,6 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, space1};
-use nom::combinator::{eof, map};
-use nom::multi::separated_list1;
-use nom::sequence::tuple;
-use nom::IResult;
+use nom::character::complete::digit1;
+
+use crate::helpers::parsing::{labelled, parse_all, spaced, unsigned, ParseError};
 
 const INPUT_FILE: &str = "input/06.txt";
 
@@ -12,7 +9,7 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
+pub fn a_with_input(input: &str) -> usize {
     let races = parse_input_a(input);
 
     races.iter().map(|r| r.num_solutions()).product()
@@ -65,21 +62,17 @@ impl Race {
 }
 
 fn parse_input_a(input: &str) -> Vec<Race> {
-    fn parse_line<'a>(input: &'a str, str_tag: &str) -> IResult<&'a str, Vec<u64>> {
-        let (input, _) = tuple((tag(str_tag), tag(":"), space1))(input)?;
-        let (input, nums) =
-            separated_list1(space1, map(digit1, |d: &str| d.parse::<u64>().unwrap()))(input)?;
-        let (_, _) = eof(input)?;
-        Ok(("", nums))
+    fn parse_line<'a>(input: &'a str, str_tag: &'static str) -> Result<Vec<u64>, ParseError<'a>> {
+        parse_all(input, labelled(str_tag, spaced(unsigned)))
     }
 
     let mut line_iter = input.lines();
-    let (_, times) = line_iter
+    let times = line_iter
         .next()
         .map(|line| parse_line(line, "Time"))
         .expect("First line should exist")
         .expect("First line should parse");
-    let (_, records) = line_iter
+    let records = line_iter
         .next()
         .map(|line| parse_line(line, "Distance"))
         .expect("Second line should exist")
@@ -101,20 +94,19 @@ fn parse_input_a(input: &str) -> Vec<Race> {
 }
 
 fn parse_input_b(input: &str) -> Race {
-    fn parse_line<'a>(input: &'a str, str_tag: &str) -> IResult<&'a str, u64> {
-        let (input, _) = tuple((tag(str_tag), tag(":"), space1))(input)?;
-        let joined: String = input.chars().filter(|c| !c.is_ascii_whitespace()).collect();
-        let num: u64 = joined.parse().unwrap();
-        Ok(("", num))
+    fn parse_line<'a>(input: &'a str, str_tag: &'static str) -> Result<u64, ParseError<'a>> {
+        let digits = parse_all(input, labelled(str_tag, spaced(digit1)))?;
+        let joined: String = digits.concat();
+        Ok(joined.parse().expect("Joined number should fit in a u64"))
     }
 
     let mut line_iter = input.lines();
-    let (_, time) = line_iter
+    let time = line_iter
         .next()
         .map(|line| parse_line(line, "Time"))
         .expect("First line should exist")
         .expect("First line should parse");
-    let (_, record) = line_iter
+    let record = line_iter
         .next()
         .map(|line| parse_line(line, "Distance"))
         .expect("Second line should exist")
@@ -129,7 +121,7 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
+pub fn b_with_input(input: &str) -> usize {
     let race = parse_input_b(input);
     race.num_solutions_fast()
 }
@@ -138,7 +130,7 @@ fn b_with_input(input: &str) -> usize {
 mod tests {
     use super::*;
 
-    const SAMPLE_A: &'static str = "Time:      7  15   30
+    const SAMPLE_A: &str = "Time:      7  15   30
 Distance:  9  40  200";
 
     #[test]
@@ -1,3 +1,5 @@
+use crate::helpers::wide::Wide;
+
 const INPUT_FILE: &str = "input/07.txt";
 
 pub fn a() -> String {
@@ -5,14 +7,14 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
+pub fn a_with_input(input: &str) -> Wide {
     let mut hands: Vec<_> = input.lines().map(part_a::parse_line).collect();
     hands.sort();
 
-    let total: usize = hands
+    let total: Wide = hands
         .iter()
         .enumerate()
-        .map(|(rank, (_, bid))| (rank + 1) * bid)
+        .map(|(rank, (_, bid))| Wide::from(rank + 1) * Wide::from(*bid))
         .sum();
 
     total
@@ -23,14 +25,14 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
+pub fn b_with_input(input: &str) -> Wide {
     let mut hands: Vec<_> = input.lines().map(part_b::parse_line).collect();
     hands.sort();
 
-    let total: usize = hands
+    let total: Wide = hands
         .iter()
         .enumerate()
-        .map(|(rank, (_, bid))| (rank + 1) * bid)
+        .map(|(rank, (_, bid))| Wide::from(rank + 1) * Wide::from(*bid))
         .sum();
 
     total
@@ -344,7 +346,7 @@ mod tests {
     use super::part_b::HandType as HandTypeB;
     use super::*;
 
-    const SAMPLE_A: &'static str = "32T3K 765
+    const SAMPLE_A: &str = "32T3K 765
 T55J5 684
 KK677 28
 KTJJT 220
@@ -352,12 +354,12 @@ QQQJA 483";
 
     #[test]
     fn sample_a() {
-        assert_eq!(a_with_input(SAMPLE_A), 6440);
+        assert_eq!(a_with_input(SAMPLE_A), Wide::from(6440));
     }
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_A), 5905);
+        assert_eq!(b_with_input(SAMPLE_A), Wide::from(5905));
     }
 
     fn hand_type_test_b(input: &str, exp: HandTypeB) {
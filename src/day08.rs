@@ -6,6 +6,12 @@ use nom::character::complete::alphanumeric1;
 use nom::combinator::eof;
 use nom::IResult;
 
+use crate::helpers::cycles;
+use crate::helpers::number_theory::{crt, crt_big, Congruence, CrtError};
+use crate::helpers::wide::Wide;
+use crate::params::{Param, Params};
+use crate::solutions::SharedParse;
+
 const INPUT_FILE: &str = "input/08.txt";
 
 pub fn a() -> String {
@@ -13,21 +19,96 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
+pub const PARAMS_A: &[Param] = &[
+    Param {
+        key: "start",
+        default: "AAA",
+        description: "node to start from",
+    },
+    Param {
+        key: "end",
+        default: "ZZZ",
+        description: "node to walk to",
+    },
+];
+
+pub const PARAMS_B: &[Param] = &[
+    Param {
+        key: "start_suffix",
+        default: "A",
+        description: "last character of the nodes the ghosts start from",
+    },
+    Param {
+        key: "end_suffix",
+        default: "Z",
+        description: "last character of the nodes the ghosts walk to",
+    },
+];
+
+pub fn a_with_input(input: &str) -> usize {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
+}
+
+pub fn a_with_params(input: &str, params: &Params) -> Result<usize, String> {
+    solve_a(&parse_input(input)?, params)
+}
+
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = ParseResult;
+
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+    const PARAMS_B: &'static [Param] = PARAMS_B;
+
+    fn parse(input: &str) -> Result<ParseResult, String> {
+        parse_input(input)
+    }
+
+    fn a(parsed: &ParseResult, params: &Params) -> Result<String, String> {
+        solve_a(parsed, params).map(|n| n.to_string())
+    }
+
+    fn b(parsed: &ParseResult, params: &Params) -> Result<String, String> {
+        solve_b(parsed, params).map(|n| n.to_string())
+    }
+}
+
+fn solve_a(parsed: &ParseResult, params: &Params) -> Result<usize, String> {
     let ParseResult {
         moves,
         map,
         name_lookup,
-    } = parse_input(input);
+    } = parsed;
+
+    let lookup = |key: &str| -> Result<usize, String> {
+        let name: String = params.get(key)?;
+        name_lookup
+            .get(&name)
+            .copied()
+            .ok_or_else(|| format!("There is no node called '{}'", name))
+    };
 
-    let mut curr_node = name_lookup.get("AAA").copied().unwrap();
-    let target = name_lookup.get("ZZZ").copied().unwrap();
+    let mut curr_node = lookup("start")?;
+    let target = lookup("end")?;
 
     let mut move_idx = 0;
 
     let mut num_moves = 0;
 
+    // there are only so many (node, move) states, so once we've taken that many steps without
+    // arriving we're going round in circles
+    let max_moves = name_lookup.len() * moves.len();
+
     while curr_node != target {
+        if num_moves >= max_moves {
+            return Err(format!(
+                "'{}' never reaches '{}'",
+                params.get::<String>("start")?,
+                params.get::<String>("end")?
+            ));
+        }
+
         let next_move = moves[move_idx];
 
         let options = map[&curr_node];
@@ -44,7 +125,7 @@ fn a_with_input(input: &str) -> usize {
         }
     }
 
-    num_moves
+    Ok(num_moves)
 }
 
 pub fn b() -> String {
@@ -86,34 +167,16 @@ fn find_period(
         panic!("can't have empty moves list");
     }
 
-    let mut state = MoveState {
+    let state = MoveState {
         move_idx: 0,
         pos: start,
     };
 
-    let mut iters = 0;
-
-    let mut seen: HashSet<MoveState> = HashSet::default();
-
-    while seen.insert(state) {
-        state.next(moves, map);
-        iters += 1;
-    }
-
-    let first_repeat_iters = iters;
-    let goal_state = state;
-
-    while iters == first_repeat_iters || state != goal_state {
-        state.next(moves, map);
-        iters += 1;
-    }
-
-    let second_repeat_iters = iters;
-
-    let period = second_repeat_iters - first_repeat_iters;
-    let offset = first_repeat_iters - period;
-
-    (offset, period)
+    cycles::find_cycle_brent(state, |s| {
+        let mut next = *s;
+        next.next(moves, map);
+        next
+    })
 }
 
 /// Given the initial position and offset, find the points (in the repeating period) where the
@@ -148,33 +211,43 @@ fn find_target_times(
     target_times
 }
 
-fn lcm(a: i128, b: i128) -> i128 {
-    let g = ring_algorithm::gcd(a, b);
-    (a / g) * b
+pub fn b_with_input(input: &str) -> Wide {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
+}
+
+pub fn b_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    solve_b(&parse_input(input)?, params)
 }
 
-fn b_with_input(input: &str) -> i128 {
+fn solve_b(parsed: &ParseResult, params: &Params) -> Result<Wide, String> {
     let ParseResult {
         moves,
         map,
         name_lookup,
-    } = parse_input(input);
+    } = parsed;
+
+    let start_suffix: char = params.get("start_suffix")?;
+    let end_suffix: char = params.get("end_suffix")?;
 
     let mut is_source: HashSet<usize> = HashSet::default();
     let mut is_target: HashSet<usize> = HashSet::default();
 
     let mut current_positions: Vec<usize> = Vec::new();
 
+    // a node can be both a source and a target, e.g. if the suffixes are the same
     for (name, idx) in name_lookup.iter() {
-        if name.ends_with('A') {
+        if name.ends_with(start_suffix) {
             is_source.insert(*idx);
             current_positions.push(*idx);
-        } else if name.ends_with('Z') {
+        }
+        if name.ends_with(end_suffix) {
             is_target.insert(*idx);
         }
     }
 
-    assert_eq!(is_source.len(), is_target.len());
+    if is_source.is_empty() {
+        return Err(format!("No nodes end in '{}' to start from", start_suffix));
+    }
 
     let num_ghosts = current_positions.len();
 
@@ -182,7 +255,7 @@ fn b_with_input(input: &str) -> i128 {
     let mut max_offset = 0;
 
     for p in current_positions.iter().copied() {
-        let (offset, period) = find_period(p, &map, &moves);
+        let (offset, period) = find_period(p, map, moves);
 
         periods.push(period);
         max_offset = max_offset.max(offset);
@@ -199,11 +272,11 @@ fn b_with_input(input: &str) -> i128 {
 
         for step in 0..max_offset {
             if current_positions.iter().all(|m| is_target.contains(&m.pos)) {
-                return step as i128;
+                return Ok(Wide::from(step));
             }
             current_positions
                 .iter_mut()
-                .for_each(|m| m.next(&moves, &map));
+                .for_each(|m| m.next(moves, map));
         }
     }
 
@@ -215,8 +288,8 @@ fn b_with_input(input: &str) -> i128 {
             max_offset,
             periods[i],
             &is_target,
-            &moves,
-            &map,
+            moves,
+            map,
         );
 
         target_times.push(target_time);
@@ -224,32 +297,39 @@ fn b_with_input(input: &str) -> i128 {
 
     let periods: Vec<i128> = periods.iter().copied().map(|t| t as i128).collect();
 
-    let mut best_solution = i128::MAX;
-    let mut found_solution = false;
+    let mut best_solution: Option<Wide> = None;
 
     for targets in target_times.iter().multi_cartesian_product() {
         // at this point you need the least time where all of them are at the target; they're at the
         // target precisely if they're on step target[i] (mod period[i]) so this is a CRT thing
-        let targets: Vec<i128> = targets.into_iter().copied().map(|t| t as i128).collect();
-
-        let maybe_solution = ring_algorithm::chinese_remainder_theorem(&targets, &periods);
-
-        if let Some(found) = maybe_solution {
-            // no guarantee that's remotely in the right modulus, for some reason; need to normalize it
-            let total_modulus = periods.iter().copied().fold(1, lcm);
-            let soln = found.rem_euclid(total_modulus);
+        let congruences: Vec<Congruence> = targets
+            .into_iter()
+            .zip(periods.iter())
+            .map(|(&target, &period)| Congruence::new(target as i128, period))
+            .collect::<Result<_, _>>()
+            .expect("Periods should be positive");
+
+        let found = match crt(&congruences) {
+            Ok(found) => Some(Wide::from(found.residue)),
+            // these particular target times never line up, which is fine
+            Err(CrtError::Inconsistent(_, _)) => None,
+            // the combined period doesn't fit in an i128, so redo it the slow way
+            Err(CrtError::Overflow) => crt_big(&congruences).map(|found| Wide::from(found.residue)),
+            Err(e) => panic!("Could not combine target times: {}", e),
+        };
 
-            best_solution = best_solution.min(soln);
-            found_solution = true;
+        if let Some(found) = found {
+            if best_solution.as_ref().is_none_or(|best| found < *best) {
+                best_solution = Some(found);
+            }
         }
     }
 
-    if !found_solution {
-        panic!("No solution found!");
-    }
+    let best_solution = best_solution
+        .ok_or_else(|| "The ghosts never all reach their targets at the same time".to_string())?;
 
     // don't forget the offset we skipped at the beginning
-    best_solution + (max_offset as i128)
+    Ok(best_solution + Wide::from(max_offset))
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
@@ -258,76 +338,90 @@ enum Turn {
     R,
 }
 
-struct ParseResult {
+pub struct ParseResult {
     moves: Vec<Turn>,
     map: HashMap<usize, (usize, usize)>,
     name_lookup: HashMap<String, usize>,
 }
 
-fn parse_input(input: &str) -> ParseResult {
-    fn parse(input: &str) -> IResult<&str, ParseResult> {
-        let mut lines = input.lines();
-
-        let moves: Vec<Turn> = lines
-            .next()
-            .unwrap()
-            .chars()
-            .map(|c| {
-                if c == 'R' {
-                    Turn::R
-                } else if c == 'L' {
-                    Turn::L
-                } else {
-                    panic!("BAD MOVE CHAR {c}")
-                }
-            })
-            .collect();
+fn parse_input(input: &str) -> Result<ParseResult, String> {
+    fn parse_node(line: &str) -> IResult<&str, (&str, &str, &str)> {
+        let (line, source_name) = alphanumeric1(line)?;
+        let (line, _) = tag(" = (")(line)?;
+        let (line, l_name) = alphanumeric1(line)?;
+        let (line, _) = tag(", ")(line)?;
+        let (line, r_name) = alphanumeric1(line)?;
+        let (line, _) = tag(")")(line)?;
+        let (line, _) = eof(line)?;
+
+        Ok((line, (source_name, l_name, r_name)))
+    }
 
-        assert_eq!(lines.next(), Some(""));
+    let mut lines = input.lines();
 
-        let mut name_to_index: HashMap<String, usize> = HashMap::default();
+    let moves: Vec<Turn> = lines
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'R' => Ok(Turn::R),
+            'L' => Ok(Turn::L),
+            other => Err(format!("Bad move '{}', expected 'L' or 'R'", other)),
+        })
+        .collect::<Result<_, _>>()?;
 
-        let mut next_id = |name: &str| -> usize {
-            let len_now = name_to_index.len();
-            *name_to_index.entry(name.to_string()).or_insert(len_now)
-        };
+    if moves.is_empty() {
+        return Err("The first line should be a nonempty list of moves".to_string());
+    }
+
+    if lines.next() != Some("") {
+        return Err("The moves should be followed by a blank line".to_string());
+    }
+
+    let mut name_to_index: HashMap<String, usize> = HashMap::default();
+
+    let mut next_id = |name: &str| -> usize {
+        let len_now = name_to_index.len();
+        *name_to_index.entry(name.to_string()).or_insert(len_now)
+    };
 
-        let mut map = HashMap::default();
+    let mut map = HashMap::default();
 
-        for line in lines {
-            let (line, source_name) = alphanumeric1(line)?;
-            let (line, _) = tag(" = (")(line)?;
-            let (line, l_name) = alphanumeric1(line)?;
-            let (line, _) = tag(", ")(line)?;
-            let (line, r_name) = alphanumeric1(line)?;
-            let (line, _) = tag(")")(line)?;
-            let (_, _) = eof(line)?;
+    for line in lines {
+        let (_, (source_name, l_name, r_name)) =
+            parse_node(line).map_err(|e| format!("Cannot parse node '{}': {}", line, e))?;
 
-            let source_idx = next_id(source_name);
-            let l_idx = next_id(l_name);
-            let r_idx = next_id(r_name);
+        let source_idx = next_id(source_name);
+        let l_idx = next_id(l_name);
+        let r_idx = next_id(r_name);
 
-            map.insert(source_idx, (l_idx, r_idx));
+        if map.insert(source_idx, (l_idx, r_idx)).is_some() {
+            return Err(format!("Node '{}' is defined twice", source_name));
         }
+    }
 
-        Ok((
-            "",
-            ParseResult {
-                moves,
-                map,
-                name_lookup: name_to_index,
-            },
-        ))
+    // every node we can walk to needs somewhere to go next
+    if let Some(name) = name_to_index
+        .iter()
+        .filter(|(_, idx)| !map.contains_key(idx))
+        .map(|(name, _)| name)
+        .min()
+    {
+        return Err(format!("Node '{}' is used but never defined", name));
     }
 
-    parse(input).unwrap().1
+    Ok(ParseResult {
+        moves,
+        map,
+        name_lookup: name_to_index,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE_1: &'static str = "RL
+    const SAMPLE_1: &str = "RL
 
 AAA = (BBB, CCC)
 BBB = (DDD, EEE)
@@ -337,7 +431,7 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)";
 
-    const SAMPLE_2: &'static str = "LLR
+    const SAMPLE_2: &str = "LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
@@ -353,7 +447,7 @@ ZZZ = (ZZZ, ZZZ)";
         assert_eq!(a_with_input(SAMPLE_2), 6);
     }
 
-    const SAMPLE_B: &'static str = "LR
+    const SAMPLE_B: &str = "LR
 
 11A = (11B, XXX)
 11B = (XXX, 11Z)
@@ -366,6 +460,91 @@ XXX = (XXX, XXX)";
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE_B), 6);
+        assert_eq!(b_with_input(SAMPLE_B), Wide::from(6));
+    }
+
+    #[test]
+    fn unreachable_end() {
+        let params = Params::with_overrides(
+            PARAMS_A,
+            &[("start".into(), "DDD".into()), ("end".into(), "ZZZ".into())],
+        );
+        assert_eq!(
+            a_with_params(SAMPLE_1, &params),
+            Err("'DDD' never reaches 'ZZZ'".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_suffixes() {
+        let with = |start: &str, end: &str| {
+            Params::with_overrides(
+                PARAMS_B,
+                &[
+                    ("start_suffix".into(), start.into()),
+                    ("end_suffix".into(), end.into()),
+                ],
+            )
+        };
+
+        assert_eq!(
+            b_with_params(SAMPLE_B, &with("Q", "Z")),
+            Err("No nodes end in 'Q' to start from".to_string())
+        );
+        assert_eq!(
+            b_with_params(SAMPLE_B, &with("A", "X")),
+            Err("The ghosts never all reach their targets at the same time".to_string())
+        );
+
+        // one ghost is only ever on its Z at odd times, the other only at even times
+        let out_of_step = "L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+11B = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22B, 22B)";
+        assert_eq!(
+            b_with_params(out_of_step, &with("A", "Z")),
+            Err("The ghosts never all reach their targets at the same time".to_string())
+        );
+    }
+
+    #[test]
+    fn sources_can_be_targets() {
+        let params = Params::with_overrides(
+            PARAMS_B,
+            &[
+                ("start_suffix".into(), "B".into()),
+                ("end_suffix".into(), "B".into()),
+            ],
+        );
+        // every ghost starts on a target
+        assert_eq!(b_with_params(SAMPLE_B, &params), Ok(Wide::from(0)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |input: &str| a_with_params(input, &Params::defaults(PARAMS_A)).unwrap_err();
+
+        assert_eq!(err(""), "The first line should be a nonempty list of moves");
+        assert_eq!(
+            err("LRX\n\nAAA = (AAA, AAA)"),
+            "Bad move 'X', expected 'L' or 'R'"
+        );
+        assert_eq!(
+            err("LR\nAAA = (AAA, AAA)"),
+            "The moves should be followed by a blank line"
+        );
+        assert!(err("LR\n\nAAA = AAA, AAA").starts_with("Cannot parse node 'AAA = AAA, AAA'"));
+        assert_eq!(
+            err("LR\n\nAAA = (BBB, BBB)\nAAA = (AAA, AAA)"),
+            "Node 'AAA' is defined twice"
+        );
+        assert_eq!(
+            err("LR\n\nAAA = (BBB, ZZZ)"),
+            "Node 'BBB' is used but never defined"
+        );
     }
 }
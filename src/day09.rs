@@ -1,3 +1,9 @@
+use crate::helpers::parsing::{parse_all, signed, spaced};
+use crate::helpers::polynomial::Newton;
+use crate::helpers::wide::Wide;
+use crate::params::Params;
+use crate::solutions::SharedParse;
+
 const INPUT_FILE: &str = "input/09.txt";
 
 pub fn a() -> String {
@@ -5,29 +11,26 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> i64 {
-    input
-        .lines()
-        .map(parse_line)
-        .map(|v| extrapolate_next(&v))
-        .sum()
+pub fn a_with_input(input: &str) -> Wide {
+    sum_next(&parse_lines(input))
 }
 
-fn parse_line(line: &str) -> Vec<i64> {
-    line.split_ascii_whitespace()
-        .map(|n| n.parse::<i64>().unwrap())
-        .collect()
+fn sum_next(sequences: &[Vec<i64>]) -> Wide {
+    sequences.iter().map(|v| extrapolate_next(v, 1)).sum()
 }
 
-fn extrapolate_next(nums: &[i64]) -> i64 {
-    if nums.iter().copied().all(|z| z == 0) {
-        return 0;
-    }
+fn parse_lines(input: &str) -> Vec<Vec<i64>> {
+    input.lines().map(parse_line).collect()
+}
 
-    let diffs: Vec<i64> = nums.windows(2).map(|arr| arr[1] - arr[0]).collect();
-    let next_diff = extrapolate_next(&diffs);
+fn parse_line(line: &str) -> Vec<i64> {
+    parse_all(line, spaced(signed)).expect("Line should be numbers")
+}
 
-    nums[nums.len() - 1] + next_diff
+/// The value `steps` places past the end of the sequence
+fn extrapolate_next(nums: &[i64], steps: usize) -> Wide {
+    let end = nums.len() as i64 - 1;
+    Newton::from_values(nums).value_at(end + steps as i64)
 }
 
 pub fn b() -> String {
@@ -35,40 +38,66 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> i64 {
-    input
-        .lines()
-        .map(parse_line)
-        .map(|v| extrapolate_prev(&v))
-        .sum()
+pub fn b_with_input(input: &str) -> Wide {
+    sum_prev(&parse_lines(input))
+}
+
+fn sum_prev(sequences: &[Vec<i64>]) -> Wide {
+    sequences.iter().map(|v| extrapolate_prev(v, 1)).sum()
 }
 
-fn extrapolate_prev(nums: &[i64]) -> i64 {
-    if nums.iter().copied().all(|z| z == 0) {
-        return 0;
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Vec<Vec<i64>>, String> {
+        Ok(parse_lines(input))
     }
 
-    let diffs: Vec<i64> = nums.windows(2).map(|arr| arr[1] - arr[0]).collect();
-    let next_diff = extrapolate_prev(&diffs);
+    fn a(sequences: &Vec<Vec<i64>>, _params: &Params) -> Result<String, String> {
+        Ok(sum_next(sequences).to_string())
+    }
 
-    nums[0] - next_diff
+    fn b(sequences: &Vec<Vec<i64>>, _params: &Params) -> Result<String, String> {
+        Ok(sum_prev(sequences).to_string())
+    }
+}
+
+/// The value `steps` places before the start of the sequence
+fn extrapolate_prev(nums: &[i64], steps: usize) -> Wide {
+    Newton::from_values(nums).value_at(-(steps as i64))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const SAMPLE: &'static str = "0 3 6 9 12 15
+    const SAMPLE: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45";
 
     #[test]
     fn sample_a() {
-        assert_eq!(a_with_input(SAMPLE), 114);
+        assert_eq!(a_with_input(SAMPLE), Wide::from(114));
     }
 
     #[test]
     fn sample_b() {
-        assert_eq!(b_with_input(SAMPLE), 2);
+        assert_eq!(b_with_input(SAMPLE), Wide::from(2));
+    }
+
+    #[test]
+    fn further_steps() {
+        let nums = parse_line("10 13 16 21 30 45");
+
+        // continuing the table by hand: 68, 101, 146, ...
+        assert_eq!(extrapolate_next(&nums, 1), Wide::from(68));
+        assert_eq!(extrapolate_next(&nums, 2), Wide::from(101));
+        assert_eq!(extrapolate_next(&nums, 3), Wide::from(146));
+
+        // and backwards: 5, then -4
+        assert_eq!(extrapolate_prev(&nums, 1), Wide::from(5));
+        assert_eq!(extrapolate_prev(&nums, 2), Wide::from(-4));
     }
 }
@@ -1,5 +1,11 @@
-use ahash::{HashMap, HashSet};
-use std::collections::VecDeque;
+use ahash::HashSet;
+
+use crate::helpers::grid::Grid;
+use crate::helpers::search::{bfs, flood_fill, Paths};
+use crate::params::Params;
+use crate::render::{Canvas, Colour};
+use crate::solutions::SharedParse;
+use crate::svg::Svg;
 
 const INPUT_FILE: &str = "input/10.txt";
 
@@ -8,40 +14,80 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    let map = parse(input);
+pub fn a_with_input(input: &str) -> usize {
+    farthest(&parse(input))
+}
 
-    let mut to_process = VecDeque::new();
-    let mut seen = HashSet::default();
-    to_process.push_back((map.start, 0_usize));
+/// How many steps it is round the loop to the point farthest from the start
+fn farthest(map: &Map) -> usize {
+    let reached = bfs([map.start], |pos| map.neighbours(pos), Paths::Skip);
 
-    let mut max = 0;
+    reached
+        .costs
+        .values()
+        .copied()
+        .max()
+        .expect("Start should be reached")
+}
 
-    while let Some((pos, cost)) = to_process.pop_front() {
-        if !seen.insert(pos) {
-            continue;
-        }
+pub fn b() -> String {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    b_with_input(&input).to_string()
+}
 
-        max = max.max(cost);
+pub fn b_with_input(input: &str) -> usize {
+    let map = parse(input);
+    enclosed_tiles(&map).len()
+}
 
-        if let Some(conns) = map.edges.get(&pos) {
-            for node in conns.iter().copied() {
-                to_process.push_back((node, cost + 1));
-            }
-        }
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Map;
+
+    fn parse(input: &str) -> Result<Map, String> {
+        Ok(parse(input))
+    }
+
+    fn a(map: &Map, _params: &Params) -> Result<String, String> {
+        Ok(farthest(map).to_string())
     }
 
-    max
+    fn b(map: &Map, _params: &Params) -> Result<String, String> {
+        Ok(enclosed_tiles(map).len().to_string())
+    }
 }
 
-pub fn b() -> String {
-    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
-    b_with_input(&input).to_string()
+/// All the (small) positions which are part of the main loop
+fn loop_tiles(map: &Map) -> HashSet<Pos> {
+    flood_fill([map.start], |pos| map.neighbours(pos))
 }
 
-fn b_with_input(input: &str) -> usize {
-    let map = parse(input);
+/// The main loop in the order you'd walk it, starting (but not ending) at the start
+fn loop_path(map: &Map) -> Vec<Pos> {
+    let mut path = vec![map.start];
+
+    let mut prev = map.start;
+    let mut curr = map.edges[map.start][0];
+
+    while curr != map.start {
+        path.push(curr);
+
+        let next = map.edges[curr]
+            .iter()
+            .copied()
+            .find(|&p| p != prev)
+            .expect("Main loop should not dead-end");
+
+        prev = curr;
+        curr = next;
+    }
+
+    path
+}
 
+/// All the (small) positions which are strictly enclosed by the main loop
+fn enclosed_tiles(map: &Map) -> Vec<Pos> {
     // Basic idea:
     //      Double the resolution of the grid and add a buffer on the outside
     //          -- so (x, y) in the original grid maps to (2x+1, 2y+1)
@@ -56,225 +102,181 @@ fn b_with_input(input: &str) -> usize {
     //      Final answer is total_space - reachable_from_outside - num_main_loop_tiles, all computed
     //          in the final (original size) grid
 
-    let small_to_big = |p: Pos| -> Pos {
-        let Pos { x, y } = p;
-        Pos {
-            x: x * 2 + 1,
-            y: y * 2 + 1,
-        }
-    };
+    let small_to_big = |(x, y): Pos| -> Pos { (x * 2 + 1, y * 2 + 1) };
 
     // PRE: a and b were adjacent in small coordinates, but are now big (and thus odd, and differ by two)
-    let between_to_big = |a: Pos, b: Pos| -> Pos {
-        Pos {
-            x: (a.x + b.x) / 2,
-            y: (a.y + b.y) / 2,
-        }
-    };
+    let between_to_big = |a: Pos, b: Pos| -> Pos { ((a.0 + b.0) / 2, (a.1 + b.1) / 2) };
 
-    let big_width = map.width * 2 + 2;
-    let big_height = map.height * 2 + 2;
+    // big points that are part of the main loop
+    let mut main_loop_big = Grid::new(map.width() * 2 + 2, map.height() * 2 + 2, false);
 
-    let main_loop_pts_big_grid: HashSet<Pos> = {
-        // big points that are part of the main loop
-        let mut seen = HashSet::default();
+    for node in loop_tiles(map) {
+        let node_big = small_to_big(node);
+        main_loop_big[node_big] = true;
 
-        // small points from the map
-        let mut to_process = VecDeque::new();
-
-        to_process.push_back(map.start);
-
-        while let Some(node) = to_process.pop_front() {
-            let node_big = small_to_big(node);
-            if !seen.insert(node_big) {
-                continue;
-            }
-
-            for conn in map.edges.get(&node).unwrap().iter().copied() {
-                let conn_big = small_to_big(conn);
-                let edge = between_to_big(node_big, conn_big);
-                seen.insert(edge); // likely redundant, it's fine
-
-                to_process.push_back(conn);
-            }
+        for conn in map.neighbours(&node) {
+            main_loop_big[between_to_big(node_big, small_to_big(conn))] = true;
         }
+    }
 
-        seen
+    let outside_reachable = {
+        let (last_x, last_y) = (main_loop_big.width() - 1, main_loop_big.height() - 1);
+        let border = main_loop_big
+            .positions()
+            .filter(|&(x, y)| x == 0 || y == 0 || x == last_x || y == last_y);
+
+        flood_fill(border, |&node| {
+            main_loop_big
+                .neighbours_4(node)
+                .filter(|&next| !main_loop_big[next])
+                .collect::<Vec<Pos>>()
+        })
     };
 
-    let is_outside_reachable = {
-        let mut reachable = vec![vec![false; big_width]; big_height];
-
-        let mut to_process = VecDeque::new();
-        for x in 0..big_width {
-            to_process.push_back(Pos { x, y: 0 });
-            to_process.push_back(Pos {
-                x,
-                y: big_height - 1,
-            });
-        }
-        for y in 0..big_height {
-            to_process.push_back(Pos { x: 0, y });
-            to_process.push_back(Pos {
-                x: big_width - 1,
-                y,
-            });
-        }
+    map.edges
+        .positions()
+        .filter(|&pos| {
+            let big_pos = small_to_big(pos);
+            !main_loop_big[big_pos] && !outside_reachable.contains(&big_pos)
+        })
+        .collect()
+}
 
-        while let Some(node) = to_process.pop_front() {
-            // already been processed
-            if reachable[node.y][node.x] {
-                continue;
-            }
+pub fn render() -> Canvas {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    render_with_input(&input)
+}
 
-            reachable[node.y][node.x] = true;
-
-            for next_node in vec![node.left(), node.right(), node.up(), node.down()]
-                .into_iter()
-                .flatten()
-            {
-                if next_node.x < big_width
-                    && next_node.y < big_height
-                    && !main_loop_pts_big_grid.contains(&next_node)
-                {
-                    to_process.push_back(next_node);
-                }
+fn render_with_input(input: &str) -> Canvas {
+    let map = parse(input);
+    let main_loop = loop_tiles(&map);
+    let enclosed: HashSet<Pos> = enclosed_tiles(&map).into_iter().collect();
+
+    let mut canvas = Canvas::from_text(input);
+
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            let pos = (x, y);
+            if pos == map.start {
+                canvas.paint(x, y, Colour::Magenta);
+            } else if main_loop.contains(&pos) {
+                canvas.paint(x, y, Colour::Green);
+            } else if enclosed.contains(&pos) {
+                canvas.mark(x, y, Colour::Yellow, 'I');
+            } else {
+                canvas.mark(x, y, Colour::Grey, 'O');
             }
         }
+    }
 
-        reachable
-    };
+    canvas.add_legend(Colour::Magenta, None, "start");
+    canvas.add_legend(Colour::Green, None, "main loop");
+    canvas.add_legend(Colour::Yellow, Some('I'), "enclosed by the loop");
+    canvas.add_legend(Colour::Grey, Some('O'), "outside the loop");
 
-    (0..map.width)
-        .map(|x| {
-            (0..map.height)
-                .filter(|&y| {
-                    let big_pos = small_to_big(Pos { x, y });
-                    !main_loop_pts_big_grid.contains(&big_pos)
-                        && !is_outside_reachable[big_pos.y][big_pos.x]
-                })
-                .count()
-        })
-        .sum()
+    canvas
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
-struct Pos {
-    x: usize,
-    y: usize,
+/// (x, y), as in `Grid`
+type Pos = (usize, usize);
+
+const UP: (isize, isize) = (0, -1);
+const RIGHT: (isize, isize) = (1, 0);
+const DOWN: (isize, isize) = (0, 1);
+const LEFT: (isize, isize) = (-1, 0);
+
+#[derive(Clone, Debug)]
+pub struct Map {
+    start: Pos,
+    // the (up to two) tiles each pipe connects to
+    edges: Grid<Vec<Pos>>,
 }
 
-impl Pos {
-    fn left(self) -> Option<Self> {
-        let Self { x, y } = self;
-        if x > 0 {
-            Some(Self { x: x - 1, y })
-        } else {
-            None
-        }
+impl Map {
+    fn neighbours(&self, pos: &Pos) -> Vec<Pos> {
+        self.edges[*pos].clone()
     }
 
-    fn right(self) -> Option<Self> {
-        let Self { x, y } = self;
-        Some(Self { x: x + 1, y })
+    fn width(&self) -> usize {
+        self.edges.width()
     }
 
-    fn up(self) -> Option<Self> {
-        let Self { x, y } = self;
-        if y > 0 {
-            Some(Self { x, y: y - 1 })
-        } else {
-            None
-        }
+    fn height(&self) -> usize {
+        self.edges.height()
     }
+}
+
+fn parse(input: &str) -> Map {
+    let mut start: Option<Pos> = None;
+
+    let grid: Grid<char> = Grid::parse(input, Some).expect("Input should be a rectangular grid");
+
+    let mut edges = grid.map(|_| Vec::new());
+
+    for (pos, c) in grid.iter() {
+        let directions = match *c {
+            '|' => vec![UP, DOWN],
+            '-' => vec![LEFT, RIGHT],
+            'L' => vec![UP, RIGHT],
+            'J' => vec![UP, LEFT],
+            '7' => vec![DOWN, LEFT],
+            'F' => vec![DOWN, RIGHT],
+            '.' => vec![],
+            'S' => {
+                start = Some(pos);
+                // we'll figure this out at the end
+                vec![]
+            }
+            other => {
+                panic!("Bad input char: {}", other);
+            }
+        };
 
-    fn down(self) -> Option<Self> {
-        let Self { x, y } = self;
-        Some(Self { x, y: y + 1 })
+        edges[pos] = directions
+            .into_iter()
+            .filter_map(|d| grid.step(pos, d))
+            .collect();
     }
+
+    let start = start.expect("Didn't find start");
+
+    // the start connects to whichever pipes connect to it
+    edges[start] = grid
+        .neighbours_4(start)
+        .filter(|next| edges[*next].contains(&start))
+        .collect();
+
+    Map { start, edges }
 }
 
-#[derive(Clone, Debug)]
-struct Map {
-    start: Pos,
-    edges: HashMap<Pos, Vec<Pos>>,
-    height: usize,
-    width: usize,
+pub fn svg() -> Svg {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    svg_with_input(&input)
 }
 
-fn parse(input: &str) -> Map {
-    let mut edges = HashMap::default();
-    let mut start: Option<Pos> = None;
+fn svg_with_input(input: &str) -> Svg {
+    const CELL: f64 = 8.0;
 
-    let height = input.lines().count();
-    let width = input.lines().next().unwrap().len(); // ASCII text
-
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            let pos = Pos { x, y };
-            let my_edges = match c {
-                '|' => {
-                    vec![pos.up(), pos.down()]
-                }
-                '-' => {
-                    vec![pos.left(), pos.right()]
-                }
-                'L' => {
-                    vec![pos.up(), pos.right()]
-                }
-                'J' => {
-                    vec![pos.up(), pos.left()]
-                }
-                '7' => {
-                    vec![pos.down(), pos.left()]
-                }
-                'F' => {
-                    vec![pos.down(), pos.right()]
-                }
-                '.' => {
-                    vec![]
-                }
-                'S' => {
-                    start = Some(pos);
-                    // we'll figure this out at the end
-                    vec![]
-                }
-                other => {
-                    panic!("Bad input char: {}", other);
-                }
-            };
-
-            let my_edges: Vec<Pos> = my_edges
-                .iter()
-                .filter_map(|p| p.as_ref().copied())
-                .collect();
-
-            edges.insert(pos, my_edges);
-        }
-    }
+    let map = parse(input);
+
+    let mut svg = Svg::new(map.width() as f64 * CELL, map.height() as f64 * CELL);
 
-    if start.is_none() {
-        panic!("Didn't find start");
+    for (x, y) in enclosed_tiles(&map) {
+        svg.rect(x as f64 * CELL, y as f64 * CELL, CELL, CELL, "#f4c542");
     }
 
-    let start = start.unwrap();
+    let center = |(x, y): Pos| ((x as f64 + 0.5) * CELL, (y as f64 + 0.5) * CELL);
 
-    let mut start_edges = Vec::with_capacity(2);
+    let mut points: Vec<(f64, f64)> = loop_path(&map).into_iter().map(center).collect();
+    // close the loop
+    points.push(center(map.start));
 
-    for (node, node_edges) in edges.iter() {
-        if node_edges.contains(&start) {
-            start_edges.push(*node);
-        }
-    }
+    svg.polyline(&points, "#2a9d3c", CELL / 4.0);
 
-    edges.insert(start, start_edges);
+    let (start_x, start_y) = center(map.start);
+    svg.circle(start_x, start_y, CELL / 3.0, "#b5179e");
 
-    Map {
-        start,
-        edges,
-        height,
-        width,
-    }
+    svg
 }
 
 #[cfg(test)]
@@ -283,7 +285,7 @@ mod tests {
 
     #[test]
     fn sample_a1() {
-        const SAMPLE_01: &'static str = "-L|F7
+        const SAMPLE_01: &str = "-L|F7
 7S-7|
 L|7||
 -L-J|
@@ -294,7 +296,7 @@ L|-JF";
 
     #[test]
     fn sample_a2() {
-        const SAMPLE_02: &'static str = "..F7.
+        const SAMPLE_02: &str = "..F7.
 .FJ|.
 SJ.L7
 |F--J
@@ -305,7 +307,7 @@ LJ...";
 
     #[test]
     fn sample_b1() {
-        const SAMPLE_03: &'static str = "...........
+        const SAMPLE_03: &str = "...........
 .S-------7.
 .|F-----7|.
 .||.....||.
@@ -324,7 +326,7 @@ LJ...";
         // not even sure where to get started here, i might actually need to simulate the squeezing?
         // maybe like ... expand the map so the squeezable space is an actual tile, flood that,
         // then shrink back down ...
-        const _SAMPLE_03: &'static str = "..........
+        const _SAMPLE_03: &str = "..........
 .S------7.
 .|F----7|.
 .||OOOO||.
@@ -335,7 +337,7 @@ LJ...";
 ..........";
 
         // unmarked
-        const SAMPLE: &'static str = "..........
+        const SAMPLE: &str = "..........
 .S------7.
 .|F----7|.
 .||....||.
@@ -350,7 +352,7 @@ LJ...";
 
     #[test]
     fn sample_b2() {
-        const SAMPLE_04: &'static str = ".F----7F7F7F7F-7....
+        const SAMPLE_04: &str = ".F----7F7F7F7F-7....
 .|F--7||||||||FJ....
 .||.FJ||||||||L7....
 FJL7L7LJLJ||LJ.L-7..
@@ -366,7 +368,7 @@ L--J.L7...LJS7F-7L7.
 
     #[test]
     fn sample_b3() {
-        const SAMPLE_05: &'static str = "FF7FSF7F7F7F7F7F---7
+        const SAMPLE_05: &str = "FF7FSF7F7F7F7F7F---7
 L|LJ||||||||||||F--J
 FL-7LJLJ||||||LJL-77
 F--JF--7||LJLJ7F7FJ-
@@ -379,4 +381,51 @@ L7JLJL-JLJLJL--JLJ.L";
 
         assert_eq!(b_with_input(SAMPLE_05), 10);
     }
+
+    #[test]
+    fn render_plain() {
+        const SAMPLE: &str = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+
+        const EXPECTED: &str = "OOOOOOOOOO
+OS------7O
+O|F----7|O
+O||OOOO||O
+O||OOOO||O
+O|L-7F-J|O
+O|II||II|O
+OL--JL--JO
+OOOOOOOOOO
+";
+
+        let rendered = render_with_input(SAMPLE).render(false);
+
+        assert!(rendered.starts_with(EXPECTED));
+    }
+
+    #[test]
+    fn loop_path_walks_whole_loop() {
+        const SAMPLE: &str = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+
+        let map = parse(SAMPLE);
+        let path = loop_path(&map);
+
+        assert_eq!(path.len(), 8);
+        assert_eq!(path[0], (1, 1));
+        assert_eq!(
+            path.iter().copied().collect::<HashSet<Pos>>(),
+            loop_tiles(&map)
+        );
+    }
 }
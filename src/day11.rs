@@ -1,4 +1,11 @@
-use ahash::{HashMap, HashSet};
+use ahash::HashMap;
+
+use crate::helpers::grid::Grid;
+use crate::helpers::wide::Wide;
+use crate::params::{Param, Params};
+use crate::render::{Canvas, Colour};
+use crate::solutions::SharedParse;
+use crate::svg::Svg;
 
 const INPUT_FILE: &str = "input/11.txt";
 
@@ -7,8 +14,24 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    expanding_galaxy(input, 2)
+pub const PARAMS_A: &[Param] = &[Param {
+    key: "expansion",
+    default: "2",
+    description: "how many rows or columns each empty one becomes",
+}];
+
+pub const PARAMS_B: &[Param] = &[Param {
+    key: "expansion",
+    default: "1000000",
+    description: "how many rows or columns each empty one becomes",
+}];
+
+pub fn a_with_input(input: &str) -> Wide {
+    a_with_params(input, &Params::defaults(PARAMS_A)).expect("Default params should be valid")
+}
+
+pub fn a_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    Ok(expanding_galaxy(input, expansion(params)?))
 }
 
 pub fn b() -> String {
@@ -16,79 +39,98 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    expanding_galaxy(input, 1000000)
+pub fn b_with_input(input: &str) -> Wide {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
 }
 
-fn expanding_galaxy(input: &str, expansion: usize) -> usize {
-    let width = input.lines().next().unwrap().len(); // ASCII input
-    let height = input.lines().count();
+pub fn b_with_params(input: &str, params: &Params) -> Result<Wide, String> {
+    Ok(expanding_galaxy(input, expansion(params)?))
+}
 
-    // True: galaxy; False: empty space
-    let grid: Vec<Vec<bool>> = input
-        .lines()
-        .map(|line| line.chars().map(|c| c == '#').collect::<Vec<bool>>())
-        .collect();
+pub struct Solver;
 
-    let (empty_rows, empty_columns, galaxy_locations) = {
-        let mut galaxy_rows = HashSet::default();
-        let mut galaxy_cols = HashSet::default();
-        let mut galaxy_locations = Vec::default();
-
-        for (y, row) in grid.iter().enumerate() {
-            for (x, is_galaxy) in row.iter().copied().enumerate() {
-                if is_galaxy {
-                    galaxy_cols.insert(x);
-                    galaxy_rows.insert(y);
-                    galaxy_locations.push((x, y));
-                }
-            }
-        }
+impl SharedParse for Solver {
+    type Model = Sky;
 
-        let empty_rows: Vec<usize> = (0..height).filter(|y| !galaxy_rows.contains(y)).collect();
-        let empty_cols: Vec<usize> = (0..width).filter(|x| !galaxy_cols.contains(x)).collect();
+    const PARAMS_A: &'static [Param] = PARAMS_A;
+    const PARAMS_B: &'static [Param] = PARAMS_B;
 
-        (empty_rows, empty_cols, galaxy_locations)
-    };
+    fn parse(input: &str) -> Result<Sky, String> {
+        Ok(parse(input))
+    }
 
-    // then get the new (expanded) galaxy positions; intentionally consumes / shadows the old one
-    let galaxy_locations: Vec<(usize, usize)> = {
-        let mut x_lookup = HashMap::default();
-        let mut y_lookup = HashMap::default();
-
-        let mut new_x = 0;
-        for old_x in 0..width {
-            if empty_columns.contains(&old_x) {
-                new_x += expansion - 1;
-            } else {
-                x_lookup.insert(old_x, new_x);
-            }
+    fn a(sky: &Sky, params: &Params) -> Result<String, String> {
+        Ok(expanded_distance(sky, expansion(params)?).to_string())
+    }
 
-            new_x += 1;
-        }
+    fn b(sky: &Sky, params: &Params) -> Result<String, String> {
+        Ok(expanded_distance(sky, expansion(params)?).to_string())
+    }
+}
 
-        let mut new_y = 0;
-        for old_y in 0..height {
-            if empty_rows.contains(&old_y) {
-                new_y += expansion - 1;
-            } else {
-                y_lookup.insert(old_y, new_y);
-            }
+fn expansion(params: &Params) -> Result<usize, String> {
+    match params.get("expansion")? {
+        0 => Err("Empty space can't shrink to nothing; expansion should be at least 1".to_string()),
+        expansion => Ok(expansion),
+    }
+}
 
-            new_y += 1;
-        }
+pub struct Sky {
+    width: usize,
+    height: usize,
+    // (x, y) of each galaxy, in reading order
+    galaxy_locations: Vec<(usize, usize)>,
+    empty_rows: Vec<usize>,
+    empty_columns: Vec<usize>,
+}
 
-        galaxy_locations
-            .into_iter()
-            .map(|(old_x, old_y)| {
-                (
-                    x_lookup.get(&old_x).copied().unwrap(),
-                    y_lookup.get(&old_y).copied().unwrap(),
-                )
-            })
-            .collect()
-    };
+fn parse(input: &str) -> Sky {
+    // True: galaxy; False: empty space
+    let grid: Grid<bool> = Grid::parse(input, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
+    .expect("Input should be a grid of '#' and '.'");
+
+    let galaxy_locations = grid
+        .iter()
+        .filter(|(_, is_galaxy)| **is_galaxy)
+        .map(|(pos, _)| pos)
+        .collect();
+
+    let empty_rows: Vec<usize> = (0..grid.height())
+        .filter(|&y| !grid.row(y).contains(&true))
+        .collect();
+    let empty_columns: Vec<usize> = (0..grid.width())
+        .filter(|&x| !grid.column(x).any(|is_galaxy| *is_galaxy))
+        .collect();
 
+    Sky {
+        width: grid.width(),
+        height: grid.height(),
+        galaxy_locations,
+        empty_rows,
+        empty_columns,
+    }
+}
+
+fn expanding_galaxy(input: &str, expansion: usize) -> Wide {
+    expanded_distance(&parse(input), expansion)
+}
+
+fn expanded_distance(sky: &Sky, expansion: usize) -> Wide {
+    // every empty row or column a path crosses adds (expansion - 1) to its length, so the total
+    // is (total with no expansion) + (expansion - 1) * (number of crossings); working it out
+    // that way keeps the big multiplication out of the coordinates, where it could overflow
+    let unexpanded = total_distance(&expand(sky, 1));
+    let crossings = total_distance(&expand(sky, 2)) - unexpanded;
+
+    Wide::from(unexpanded) + Wide::from(expansion - 1) * Wide::from(crossings)
+}
+
+/// Sum of the distances between each pair of galaxies
+fn total_distance(galaxy_locations: &[(usize, usize)]) -> usize {
     let mut total_dist = 0;
 
     for i in 1..galaxy_locations.len() {
@@ -106,6 +148,148 @@ fn expanding_galaxy(input: &str, expansion: usize) -> usize {
     total_dist
 }
 
+/// The new (expanded) galaxy positions, in the same order as `sky.galaxy_locations`
+fn expand(sky: &Sky, expansion: usize) -> Vec<(usize, usize)> {
+    let mut x_lookup = HashMap::default();
+    let mut y_lookup = HashMap::default();
+
+    let mut new_x = 0;
+    for old_x in 0..sky.width {
+        if sky.empty_columns.contains(&old_x) {
+            new_x += expansion - 1;
+        } else {
+            x_lookup.insert(old_x, new_x);
+        }
+
+        new_x += 1;
+    }
+
+    let mut new_y = 0;
+    for old_y in 0..sky.height {
+        if sky.empty_rows.contains(&old_y) {
+            new_y += expansion - 1;
+        } else {
+            y_lookup.insert(old_y, new_y);
+        }
+
+        new_y += 1;
+    }
+
+    sky.galaxy_locations
+        .iter()
+        .map(|(old_x, old_y)| {
+            (
+                x_lookup.get(old_x).copied().unwrap(),
+                y_lookup.get(old_y).copied().unwrap(),
+            )
+        })
+        .collect()
+}
+
+pub fn render() -> Canvas {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    render_with_input(&input)
+}
+
+fn render_with_input(input: &str) -> Canvas {
+    let sky = parse(input);
+
+    let mut canvas = Canvas::from_text(input);
+
+    for y in 0..sky.height {
+        for x in 0..sky.width {
+            let empty_row = sky.empty_rows.contains(&y);
+            let empty_col = sky.empty_columns.contains(&x);
+
+            match (empty_row, empty_col) {
+                (true, true) => canvas.mark(x, y, Colour::Blue, '+'),
+                (true, false) => canvas.mark(x, y, Colour::Blue, '-'),
+                (false, true) => canvas.mark(x, y, Colour::Blue, '|'),
+                (false, false) => {}
+            }
+        }
+    }
+
+    for (x, y) in sky.galaxy_locations.iter().copied() {
+        canvas.paint(x, y, Colour::Yellow);
+    }
+
+    canvas.add_legend(Colour::Yellow, None, "galaxy");
+    canvas.add_legend(
+        Colour::Blue,
+        Some('-'),
+        "empty row or column (expands; '|' for columns, '+' for both)",
+    );
+
+    canvas
+}
+
+pub fn svg() -> Svg {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    svg_with_input(&input)
+}
+
+/// Draws the sky before expansion (with the empty rows and columns shaded) next to the sky after
+/// expanding by a factor of two, as in part a
+fn svg_with_input(input: &str) -> Svg {
+    const CELL: f64 = 6.0;
+    const GAP: f64 = 4.0 * CELL;
+    const LABEL: f64 = 3.0 * CELL;
+
+    let sky = parse(input);
+    let expanded = expand(&sky, 2);
+
+    let expanded_width = sky.width + sky.empty_columns.len();
+    let expanded_height = sky.height + sky.empty_rows.len();
+
+    let after_x = sky.width as f64 * CELL + GAP;
+
+    let mut svg = Svg::new(
+        after_x + expanded_width as f64 * CELL,
+        LABEL + expanded_height as f64 * CELL,
+    );
+
+    svg.text(0.0, LABEL * 0.6, LABEL * 0.5, "before expansion");
+    svg.text(after_x, LABEL * 0.6, LABEL * 0.5, "after expansion");
+
+    let before_width = sky.width as f64 * CELL;
+    let before_height = sky.height as f64 * CELL;
+
+    svg.rect(0.0, LABEL, before_width, before_height, "#f2f2f2");
+    svg.rect(
+        after_x,
+        LABEL,
+        expanded_width as f64 * CELL,
+        expanded_height as f64 * CELL,
+        "#f2f2f2",
+    );
+
+    for y in sky.empty_rows.iter().copied() {
+        svg.rect(0.0, LABEL + y as f64 * CELL, before_width, CELL, "#b8d4f0");
+    }
+    for x in sky.empty_columns.iter().copied() {
+        svg.rect(x as f64 * CELL, LABEL, CELL, before_height, "#b8d4f0");
+    }
+
+    let center = |offset: f64, (x, y): (usize, usize)| {
+        (
+            offset + (x as f64 + 0.5) * CELL,
+            LABEL + (y as f64 + 0.5) * CELL,
+        )
+    };
+
+    for pos in sky.galaxy_locations.iter().copied() {
+        let (cx, cy) = center(0.0, pos);
+        svg.circle(cx, cy, CELL / 2.0, "#e0a100");
+    }
+    for pos in expanded.iter().copied() {
+        let (cx, cy) = center(after_x, pos);
+        svg.circle(cx, cy, CELL / 2.0, "#e0a100");
+    }
+
+    svg
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,7 +307,7 @@ mod tests {
 .......#..
 #...#.....";
 
-        assert_eq!(a_with_input(sample_str), 374);
+        assert_eq!(a_with_input(sample_str), Wide::from(374));
     }
 
     #[test]
@@ -139,7 +323,7 @@ mod tests {
 .......#..
 #...#.....";
 
-        assert_eq!(expanding_galaxy(sample_str, 10), 1030);
+        assert_eq!(expanding_galaxy(sample_str, 10), Wide::from(1030));
     }
 
     #[test]
@@ -155,6 +339,57 @@ mod tests {
 .......#..
 #...#.....";
 
-        assert_eq!(expanding_galaxy(sample_str, 100), 8410);
+        assert_eq!(expanding_galaxy(sample_str, 100), Wide::from(8410));
+    }
+
+    #[test]
+    fn huge_expansion() {
+        let sample_str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+        // 292 without expansion, and paths cross empty space 82 times; way past usize::MAX
+        let expected = 292 + (usize::MAX as u128 - 1) * 82;
+        assert_eq!(
+            expanding_galaxy(sample_str, usize::MAX),
+            Wide::from(expected)
+        );
+    }
+
+    #[test]
+    fn render_plain() {
+        let sample_str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+        let rendered = render_with_input(sample_str).render(false);
+
+        assert!(rendered.starts_with(
+            "..|#.|..|.
+..|..|.#|.
+#.|..|..|.
+--+--+--+-
+..|..|#.|.
+.#|..|..|.
+..|..|..|#
+--+--+--+-
+..|..|.#|.
+#.|.#|..|.
+"
+        ));
     }
 }
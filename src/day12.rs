@@ -1,9 +1,11 @@
 use nom::bytes::complete::{is_a, tag};
-use nom::character::complete::{digit1, space1};
-use nom::combinator::{eof, map};
+use nom::character::complete::space1;
 use nom::multi::separated_list1;
-use nom::IResult;
-use time::Instant;
+
+use crate::helpers::memo::Memo;
+use crate::helpers::parsing::{parse_all, unsigned, PResult};
+use crate::params::{Param, Params};
+use crate::solutions::SharedParse;
 
 const INPUT_FILE: &str = "input/12.txt";
 
@@ -12,12 +14,12 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
+pub fn a_with_input(input: &str) -> usize {
     input.lines().map(a_line).sum()
 }
 
 fn a_line(input: &str) -> usize {
-    num_arrangements(parse(input))
+    num_arrangements(&parse(input))
 }
 
 pub fn b() -> String {
@@ -25,111 +27,128 @@ pub fn b() -> String {
     b_with_input(&input).to_string()
 }
 
-fn b_with_input(input: &str) -> usize {
-    let start = Instant::now();
-    let total_lines = input.lines().count();
-    let mut total = 0;
-    for (i, line) in input.lines().enumerate() {
-        let line_time = Instant::now();
-        total += b_line(line);
-        println!(
-            "Finished line {} of {total_lines} -- took {}; total elapsed {}",
-            i + 1,
-            line_time.elapsed(),
-            start.elapsed()
-        )
+pub const PARAMS_B: &[Param] = &[Param {
+    key: "unfold",
+    default: "5",
+    description: "copies of each record after unfolding",
+}];
+
+pub fn b_with_input(input: &str) -> usize {
+    b_with_params(input, &Params::defaults(PARAMS_B)).expect("Default params should be valid")
+}
+
+pub fn b_with_params(input: &str, params: &Params) -> Result<usize, String> {
+    let copies = unfold(params)?;
+    Ok(input.lines().map(|line| b_line(line, copies)).sum())
+}
+
+fn unfold(params: &Params) -> Result<usize, String> {
+    let copies: usize = params.get("unfold")?;
+    if copies == 0 {
+        return Err("Unfolding should leave at least one copy of each record".to_string());
     }
-    total
+    Ok(copies)
 }
 
-fn b_line(input: &str) -> usize {
+fn b_line(input: &str, copies: usize) -> usize {
     let parsed = parse(input);
-    let expanded = parsed.expand();
-    num_arrangements(expanded)
+    let expanded = parsed.expand(copies);
+    num_arrangements(&expanded)
+}
+
+pub struct Solver;
+
+impl SharedParse for Solver {
+    type Model = Vec<ParseResult>;
+
+    const PARAMS_B: &'static [Param] = PARAMS_B;
+
+    fn parse(input: &str) -> Result<Vec<ParseResult>, String> {
+        Ok(input.lines().map(parse).collect())
+    }
+
+    fn a(records: &Vec<ParseResult>, _params: &Params) -> Result<String, String> {
+        let total: usize = records.iter().map(num_arrangements).sum();
+        Ok(total.to_string())
+    }
+
+    fn b(records: &Vec<ParseResult>, params: &Params) -> Result<String, String> {
+        let copies = unfold(params)?;
+        let total: usize = records
+            .iter()
+            .map(|record| num_arrangements(&record.expand(copies)))
+            .sum();
+        Ok(total.to_string())
+    }
 }
 
-fn num_arrangements(input: ParseResult) -> usize {
+fn num_arrangements(input: &ParseResult) -> usize {
     let ParseResult {
         cells,
         damaged_counts,
     } = input;
 
-    // Start with the dumbest possible solution -- recursively try every possible assignment
-    // of Broken and NotBroken
+    // Recursively try every possible assignment of Broken and NotBroken, but remember the
+    // answer for each state, since there are far fewer states than assignments
+    //      state is (next cell to look at, next damaged run to achieve, length of the current run)
     fn count_solutions_rec(
-        cells: &mut [ParsedCell],
-        // REVERSED: so the LAST one is the next one that needs to be achieved
-        damaged_counts: &mut Vec<usize>,
-        current_run: usize,
+        cells: &[ParsedCell],
+        damaged_counts: &[usize],
+        memo: &mut Memo<(usize, usize, usize), usize>,
+        (cell_ind, run_ind, current_run): (usize, usize, usize),
     ) -> usize {
-        if cells.is_empty() {
-            return if current_run > 0 {
-                if damaged_counts.len() == 1 && damaged_counts.last() == Some(&current_run) {
-                    1
-                } else {
-                    0
-                }
-            } else {
-                if damaged_counts.is_empty() {
+        memo.get_or_compute((cell_ind, run_ind, current_run), |memo| {
+            if cell_ind == cells.len() {
+                return if current_run > 0 {
+                    if run_ind + 1 == damaged_counts.len() && damaged_counts[run_ind] == current_run
+                    {
+                        1
+                    } else {
+                        0
+                    }
+                } else if run_ind == damaged_counts.len() {
                     1
                 } else {
                     0
-                }
-            };
-        }
-
-        match cells[0] {
-            ParsedCell::Unknown => {
-                let mut total = 0;
-
-                // try operational?
-                cells[0] = ParsedCell::Operational;
-                total += count_solutions_rec(cells, damaged_counts, current_run);
-
-                // try damaged?
-                if !damaged_counts.is_empty() {
-                    let next_run = damaged_counts.last().copied().unwrap();
-                    if next_run > current_run {
-                        cells[0] = ParsedCell::Damaged;
-                        total += count_solutions_rec(cells, damaged_counts, current_run);
-                    }
-                }
-
-                // put the array back how we found it
-                cells[0] = ParsedCell::Unknown;
-
-                total
+                };
             }
-            ParsedCell::Operational => {
+
+            let operational = |memo: &mut Memo<_, _>| {
                 if current_run > 0 {
-                    if damaged_counts.last() != Some(&current_run) {
+                    if damaged_counts.get(run_ind) != Some(&current_run) {
                         return 0;
                     }
 
-                    damaged_counts.pop();
-                    let total = count_solutions_rec(&mut cells[1..], damaged_counts, 0);
-                    damaged_counts.push(current_run);
-                    total
+                    count_solutions_rec(cells, damaged_counts, memo, (cell_ind + 1, run_ind + 1, 0))
                 } else {
-                    count_solutions_rec(&mut cells[1..], damaged_counts, 0)
+                    count_solutions_rec(cells, damaged_counts, memo, (cell_ind + 1, run_ind, 0))
                 }
-            }
-            ParsedCell::Damaged => {
+            };
+
+            let damaged = |memo: &mut Memo<_, _>| {
                 // increment the current run and move on to the next thing
-                if damaged_counts.is_empty()
-                    || damaged_counts.last().copied().unwrap() <= current_run
-                {
-                    return 0;
+                match damaged_counts.get(run_ind) {
+                    Some(&next_run) if next_run > current_run => count_solutions_rec(
+                        cells,
+                        damaged_counts,
+                        memo,
+                        (cell_ind + 1, run_ind, current_run + 1),
+                    ),
+                    _ => 0,
                 }
+            };
 
-                count_solutions_rec(&mut cells[1..], damaged_counts, current_run + 1)
+            match cells[cell_ind] {
+                ParsedCell::Unknown => operational(memo) + damaged(memo),
+                ParsedCell::Operational => operational(memo),
+                ParsedCell::Damaged => damaged(memo),
             }
-        }
+        })
     }
 
-    let mut runs_reversed: Vec<usize> = damaged_counts.iter().copied().rev().collect();
+    let mut memo = Memo::new();
 
-    count_solutions_rec(&mut cells.clone(), &mut runs_reversed, 0)
+    count_solutions_rec(cells, damaged_counts, &mut memo, (0, 0, 0))
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -140,17 +159,18 @@ enum ParsedCell {
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-struct ParseResult {
+pub struct ParseResult {
     cells: Vec<ParsedCell>,
     damaged_counts: Vec<usize>,
 }
 
 impl ParseResult {
-    fn expand(&self) -> Self {
+    /// The given number of copies (at least one) of the record, separated by unknown cells
+    fn expand(&self, copies: usize) -> Self {
         let mut new_cells = self.cells.clone();
         let mut new_counts = self.damaged_counts.clone();
 
-        for _ in 0..4 {
+        for _ in 1..copies {
             new_cells.push(ParsedCell::Unknown);
             for c in self.cells.iter().copied() {
                 new_cells.push(c);
@@ -169,7 +189,7 @@ impl ParseResult {
 }
 
 fn parse(line: &str) -> ParseResult {
-    fn parse_cells(input: &str) -> IResult<&str, Vec<ParsedCell>> {
+    fn parse_cells(input: &str) -> PResult<'_, Vec<ParsedCell>> {
         let (input, cell_chars) = is_a(".#?")(input)?;
         let cells = cell_chars
             .chars()
@@ -184,17 +204,12 @@ fn parse(line: &str) -> ParseResult {
         Ok((input, cells))
     }
 
-    fn parse_nums(line: &str) -> IResult<&str, Vec<usize>> {
-        separated_list1(tag(","), map(digit1, |s: &str| s.parse::<usize>().unwrap()))(line)
-    }
-
-    fn parse_helper(line: &str) -> IResult<&str, ParseResult> {
+    fn parse_helper(line: &str) -> PResult<'_, ParseResult> {
         let (line, cells) = parse_cells(line)?;
         let (line, _) = space1(line)?;
-        let (line, damaged_counts) = parse_nums(line)?;
-        let (_, _) = eof(line)?;
+        let (line, damaged_counts) = separated_list1(tag(","), unsigned)(line)?;
         Ok((
-            "",
+            line,
             ParseResult {
                 cells,
                 damaged_counts,
@@ -202,7 +217,7 @@ fn parse(line: &str) -> ParseResult {
         ))
     }
 
-    parse_helper(line).unwrap().1
+    parse_all(line, parse_helper).expect("Line should parse")
 }
 
 #[cfg(test)]
@@ -225,7 +240,7 @@ mod tests {
 
     #[test]
     fn b_easy() {
-        assert_eq!(b_line("???.### 1,1,3"), 1);
+        assert_eq!(b_line("???.### 1,1,3", 5), 1);
     }
 
     #[test]
@@ -237,7 +252,14 @@ mod tests {
             ("????.######..#####. 1,6,5", 2500),
             ("?###???????? 3,2,1", 506250),
         ] {
-            assert_eq!(b_line(s), exp);
+            assert_eq!(b_line(s, 5), exp);
+        }
+    }
+
+    #[test]
+    fn one_copy_is_unfolded() {
+        for s in [".??..??...?##. 1,1,3", "?###???????? 3,2,1"] {
+            assert_eq!(b_line(s, 1), a_line(s));
         }
     }
 }
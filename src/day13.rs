@@ -1,3 +1,11 @@
+use nom::bytes::complete::is_a;
+use nom::combinator::map;
+
+use crate::helpers::grid::Grid;
+use crate::helpers::parsing::{blocks, lines, parse_all};
+use crate::render::{Canvas, Colour};
+use crate::svg::Svg;
+
 const INPUT_FILE: &str = "input/13.txt";
 
 pub fn a() -> String {
@@ -5,15 +13,10 @@ pub fn a() -> String {
     a_with_input(&input).to_string()
 }
 
-fn a_with_input(input: &str) -> usize {
-    parse_blocks(input.lines())
+pub fn a_with_input(input: &str) -> usize {
+    parse_blocks(input)
         .iter()
-        .enumerate()
-        .map(|(i, b)| {
-            println!("Checking block {i}");
-            b.symmetry()
-        })
-        .map(|s| s.score())
+        .map(|b| b.symmetry().score())
         .sum()
 }
 
@@ -44,23 +47,18 @@ impl Symmetry {
 #[derive(Debug, Eq, PartialEq)]
 struct Block {
     // true: ash (.); false: rocks (#)
-    // each line has the same length
-    lines: Vec<Vec<bool>>,
+    grid: Grid<bool>,
 }
 
 impl Block {
     fn symmetry(&self) -> Symmetry {
-        let width = self.lines[0].len();
-
-        for col in 1..(width) {
+        for col in 1..self.grid.width() {
             if self.is_vertical_symmetry(col) {
                 return Symmetry::Vertical { col };
             }
         }
 
-        let height = self.lines.len();
-
-        for row in 1..(height) {
+        for row in 1..self.grid.height() {
             if self.is_horizontal_symmetry(row) {
                 return Symmetry::Horizontal { row };
             }
@@ -69,94 +67,169 @@ impl Block {
         panic!("Couldn't find symmetry");
     }
 
+    /// Whether the mirror between columns `col - 1` and `col` reflects everything it reaches;
+    /// at the very edges (col 0 or col width) it trivially does
     fn is_vertical_symmetry(&self, col: usize) -> bool {
-        if col == 0 {
-            return true; // i guess?
-        }
+        let reach = col.min(self.grid.width() - col);
 
-        let width = self.lines[0].len();
-        let height = self.lines.len();
+        (0..reach).all(|i| self.grid.column(col - 1 - i).eq(self.grid.column(col + i)))
+    }
 
-        let mut rev_col = col;
-        let mut col = col - 1;
+    /// Same as `is_vertical_symmetry`, but for the mirror between rows `row - 1` and `row`
+    fn is_horizontal_symmetry(&self, row: usize) -> bool {
+        let reach = row.min(self.grid.height() - row);
 
-        if col == width - 1 {
-            return true; // i guess?
-        }
+        (0..reach).all(|i| self.grid.row(row - 1 - i) == self.grid.row(row + i))
+    }
+}
 
-        loop {
-            // check if these lines are symmetric
-            for row in 0..height {
-                if self.lines[row][col] != self.lines[row][rev_col] {
-                    return false;
-                }
-            }
+fn parse_blocks(input: &str) -> Vec<Block> {
+    let parse_block = map(lines(map(is_a(".#"), parse_line)), |rows| Block {
+        grid: Grid::from_rows(rows).expect("Blocks should be rectangular"),
+    });
 
-            // if we didn't fail and got to the end, they're symmetric
-            if col == 0 || rev_col == width - 1 {
-                return true;
-            }
+    parse_all(input, blocks(parse_block)).expect("Input should parse")
+}
 
-            col -= 1;
-            rev_col += 1;
-        }
-    }
+fn parse_line(line: &str) -> Vec<bool> {
+    line.chars().map(|c| c == '.').collect()
+}
 
-    fn is_horizontal_symmetry(&self, row: usize) -> bool {
-        if row == 0 {
-            return true; // i guess?
-        }
+pub fn render() -> Canvas {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    render_with_input(&input)
+}
 
-        let width = self.lines[0].len();
-        let height = self.lines.len();
+fn render_with_input(input: &str) -> Canvas {
+    let canvases = parse_blocks(input).iter().map(render_block).collect();
 
-        let mut rev_row = row;
-        let mut row = row - 1;
+    let mut canvas = Canvas::stack(canvases);
 
-        if row == height - 1 {
-            return true; // i guess?
-        }
+    canvas.add_legend(Colour::Magenta, None, "mirror line (between >< or v^)");
+    canvas.add_legend(Colour::Cyan, None, "reflected by the mirror");
+    canvas.add_legend(Colour::Grey, None, "no reflected counterpart");
 
-        loop {
-            for col in 0..width {
-                if self.lines[row][col] != self.lines[rev_row][col] {
-                    return false;
-                }
-            }
+    canvas
+}
 
-            if row == 0 || rev_row == height - 1 {
-                return true;
-            }
+fn render_block(block: &Block) -> Canvas {
+    let text = block
+        .grid
+        .map(|&ash| if ash { '.' } else { '#' })
+        .to_string();
+
+    let mut canvas = Canvas::from_text(&text);
+
+    // make room for the markers, like in the puzzle description
+    canvas.push_top_row();
+    canvas.push_left_column();
+
+    let width = block.grid.width();
+    let height = block.grid.height();
 
-            row -= 1;
-            rev_row += 1;
+    // the cells reflected by the mirror, in block coordinates
+    let (x_range, y_range) = match block.symmetry() {
+        Symmetry::Vertical { col } => {
+            canvas.set_char(col, 0, '>');
+            canvas.paint(col, 0, Colour::Magenta);
+            canvas.set_char(col + 1, 0, '<');
+            canvas.paint(col + 1, 0, Colour::Magenta);
+
+            let reach = col.min(width - col);
+            ((col - reach)..(col + reach), 0..height)
+        }
+        Symmetry::Horizontal { row } => {
+            canvas.set_char(0, row, 'v');
+            canvas.paint(0, row, Colour::Magenta);
+            canvas.set_char(0, row + 1, '^');
+            canvas.paint(0, row + 1, Colour::Magenta);
+
+            let reach = row.min(height - row);
+            (0..width, (row - reach)..(row + reach))
+        }
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let colour = if x_range.contains(&x) && y_range.contains(&y) {
+                Colour::Cyan
+            } else {
+                Colour::Grey
+            };
+            canvas.paint(x + 1, y + 1, colour);
         }
     }
+
+    canvas
 }
 
-fn parse_blocks<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<Block> {
-    let mut blocks: Vec<Block> = Vec::new();
+pub fn svg() -> Svg {
+    let input = std::fs::read_to_string(INPUT_FILE).expect("Input should exist");
+    svg_with_input(&input)
+}
+
+/// Draws every block (top to bottom) with its rocks and its reflection axis
+fn svg_with_input(input: &str) -> Svg {
+    const CELL: f64 = 10.0;
+    const LABEL: f64 = 2.0 * CELL;
 
-    let mut running: Vec<Vec<bool>> = Vec::new();
+    let blocks = parse_blocks(input);
+
+    let total_width = blocks.iter().map(|b| b.grid.width()).max().unwrap_or(0) as f64 * CELL;
+    let total_height: f64 = blocks
+        .iter()
+        .map(|b| LABEL + b.grid.height() as f64 * CELL)
+        .sum();
 
-    for line in lines {
-        if line.is_empty() {
-            let block = std::mem::take(&mut running);
-            blocks.push(Block { lines: block });
-        } else {
-            running.push(parse_line(line));
+    let mut svg = Svg::new(total_width, total_height);
+
+    let mut top = 0.0;
+
+    for (i, block) in blocks.iter().enumerate() {
+        let symmetry = block.symmetry();
+
+        svg.text(
+            0.0,
+            top + LABEL * 0.7,
+            LABEL * 0.6,
+            &format!("block {i}: score {}", symmetry.score()),
+        );
+        top += LABEL;
+
+        let width = block.grid.width() as f64 * CELL;
+        let height = block.grid.height() as f64 * CELL;
+
+        svg.rect(0.0, top, width, height, "#f2f2f2");
+
+        for ((x, y), is_ash) in block.grid.iter() {
+            if !is_ash {
+                svg.rect(
+                    x as f64 * CELL,
+                    top + y as f64 * CELL,
+                    CELL,
+                    CELL,
+                    "#5a5a5a",
+                );
+            }
         }
-    }
 
-    if !running.is_empty() {
-        blocks.push(Block { lines: running });
-    }
+        let (from, to) = match symmetry {
+            Symmetry::Vertical { col } => {
+                let x = col as f64 * CELL;
+                ((x, top), (x, top + height))
+            }
+            Symmetry::Horizontal { row } => {
+                let y = top + row as f64 * CELL;
+                ((0.0, y), (width, y))
+            }
+        };
 
-    blocks
-}
+        svg.line(from, to, "#d62828", CELL / 4.0);
 
-fn parse_line(line: &str) -> Vec<bool> {
-    line.chars().map(|c| c == '.').collect()
+        top += height;
+    }
+
+    svg
 }
 
 #[cfg(test)]
@@ -181,14 +254,14 @@ mod tests {
 ..##..###
 #....#..#"#;
 
-        let actual = parse_blocks(INPUT.lines());
+        let actual = parse_blocks(INPUT);
 
         let f = false;
         let t = true;
 
         let expected = vec![
             Block {
-                lines: vec![
+                grid: Grid::from_rows(vec![
                     vec![f, t, f, f, t, t, f, f, t],
                     vec![t, t, f, t, f, f, t, f, t],
                     vec![f, f, t, t, t, t, t, t, f],
@@ -196,10 +269,11 @@ mod tests {
                     vec![t, t, f, t, f, f, t, f, t],
                     vec![t, t, f, f, t, t, f, f, t],
                     vec![f, t, f, t, f, f, t, f, t],
-                ],
+                ])
+                .unwrap(),
             },
             Block {
-                lines: vec![
+                grid: Grid::from_rows(vec![
                     vec![f, t, t, t, f, f, t, t, f],
                     vec![f, t, t, t, t, f, t, t, f],
                     vec![t, t, f, f, t, t, f, f, f],
@@ -207,7 +281,8 @@ mod tests {
                     vec![f, f, f, f, f, t, f, f, t],
                     vec![t, t, f, f, t, t, f, f, f],
                     vec![f, t, t, t, t, f, t, t, f],
-                ],
+                ])
+                .unwrap(),
             },
         ];
 
@@ -256,7 +331,7 @@ mod tests {
 ..##.#...#..#..
 #..####..#..#.."#;
 
-        let mut blocks = parse_blocks(INPUT.lines());
+        let mut blocks = parse_blocks(INPUT);
 
         let block = blocks.remove(0);
 
@@ -279,7 +354,7 @@ mod tests {
 #.##..##.####
 "#;
 
-        let block = parse_blocks(INPUT.trim().lines()).remove(0);
+        let block = parse_blocks(INPUT.trim()).remove(0);
 
         assert!(block.is_vertical_symmetry(12));
 
@@ -288,4 +363,46 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn render_plain() {
+        const INPUT: &str = r#"#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#"#;
+
+        const EXPECTED: &str = "     ><
+ #.##..##.
+ ..#.##.#.
+ ##......#
+ ##......#
+ ..#.##.#.
+ ..##..##.
+ #.#.##.#.
+
+
+ #...##..#
+ #....#..#
+ ..##..###
+v#####.##.
+^#####.##.
+ ..##..###
+ #....#..#
+";
+
+        let rendered = render_with_input(INPUT).render(false);
+
+        assert!(rendered.starts_with(EXPECTED));
+    }
 }
@@ -20,8 +20,6 @@ fn b_with_input(_input: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
     #[test]
     fn hello_world() {
         assert_eq!(2 + 2, 4)
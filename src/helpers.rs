@@ -1 +1,12 @@
-
+pub mod aho_corasick;
+pub mod cycles;
+pub mod grid;
+pub mod intervals;
+pub mod json;
+pub mod memo;
+pub mod number_theory;
+pub mod parsing;
+pub mod piecewise;
+pub mod polynomial;
+pub mod search;
+pub mod wide;
@@ -0,0 +1,307 @@
+//! Finding lots of patterns at once: an Aho-Corasick automaton over bytes, which finds the first
+//! match in a single pass over the haystack, no matter how many patterns there are. Patterns are
+//! also built into a second automaton over their reversals, so the last match in a haystack can be
+//! found by scanning from the end.
+
+use std::collections::VecDeque;
+
+/// One occurrence of a pattern; `start..end` are byte offsets into the haystack
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Match {
+    /// Index into the patterns the automaton was built from
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+    // sorted by byte, so lookups can binary search
+    children: Vec<(u8, usize)>,
+    fail: usize,
+    // the pattern spelled out by the path to this node, if there is one
+    output: Option<usize>,
+    // nearest node down the failure chain with an output, so finding every match ending at a
+    // position doesn't have to walk the whole chain
+    dict: Option<usize>,
+    depth: usize,
+}
+
+impl Node {
+    fn child(&self, byte: u8) -> Option<usize> {
+        self.children
+            .binary_search_by_key(&byte, |&(b, _)| b)
+            .ok()
+            .map(|ind| self.children[ind].1)
+    }
+}
+
+/// A trie with failure links; node 0 is the root
+#[derive(Clone, Debug)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+impl Trie {
+    fn build<'a>(
+        patterns: impl Iterator<Item = (usize, Box<dyn Iterator<Item = u8> + 'a>)>,
+    ) -> Self {
+        let mut nodes = vec![Node::default()];
+
+        for (pattern, bytes) in patterns {
+            let mut state = 0;
+            for byte in bytes {
+                state = match nodes[state].child(byte) {
+                    Some(next) => next,
+                    None => {
+                        let next = nodes.len();
+                        nodes.push(Node {
+                            depth: nodes[state].depth + 1,
+                            ..Node::default()
+                        });
+
+                        let children = &mut nodes[state].children;
+                        let ind = children.partition_point(|&(b, _)| b < byte);
+                        children.insert(ind, (byte, next));
+                        next
+                    }
+                };
+            }
+
+            // if a pattern turns up twice, the first one wins
+            nodes[state].output.get_or_insert(pattern);
+        }
+
+        // failure links, shallowest first, so each node's fail target is already done
+        let mut queue: VecDeque<usize> = nodes[0].children.iter().map(|&(_, c)| c).collect();
+
+        while let Some(state) = queue.pop_front() {
+            for (byte, child) in nodes[state].children.clone() {
+                let mut fallback = nodes[state].fail;
+                let fail = loop {
+                    if let Some(next) = nodes[fallback].child(byte) {
+                        break next;
+                    }
+                    if fallback == 0 {
+                        break 0;
+                    }
+                    fallback = nodes[fallback].fail;
+                };
+
+                nodes[child].fail = fail;
+                nodes[child].dict = if nodes[fail].output.is_some() {
+                    Some(fail)
+                } else {
+                    nodes[fail].dict
+                };
+
+                queue.push_back(child);
+            }
+        }
+
+        Trie { nodes }
+    }
+
+    fn step(&self, mut state: usize, byte: u8) -> usize {
+        loop {
+            if let Some(next) = self.nodes[state].child(byte) {
+                return next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.nodes[state].fail;
+        }
+    }
+
+    /// (pattern, length) for each pattern ending at this state, longest first
+    fn outputs(&self, state: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let first = if self.nodes[state].output.is_some() {
+            Some(state)
+        } else {
+            self.nodes[state].dict
+        };
+
+        std::iter::successors(first, |&s| self.nodes[s].dict).map(|s| {
+            let node = &self.nodes[s];
+            (node.output.unwrap(), node.depth)
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AhoCorasick {
+    forward: Trie,
+    backward: Trie,
+    max_len: usize,
+    ascii_case_insensitive: bool,
+}
+
+impl AhoCorasick {
+    /// Patterns have to be non-empty. ASCII letters match regardless of case (anything else has to
+    /// match exactly).
+    pub fn ascii_case_insensitive<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        Self::build(patterns, true)
+    }
+
+    fn build<P: AsRef<[u8]>>(patterns: &[P], ascii_case_insensitive: bool) -> Self {
+        assert!(
+            patterns.iter().all(|p| !p.as_ref().is_empty()),
+            "Patterns should be non-empty"
+        );
+
+        let fold = move |b: &u8| {
+            if ascii_case_insensitive {
+                b.to_ascii_lowercase()
+            } else {
+                *b
+            }
+        };
+
+        let forward = Trie::build(patterns.iter().enumerate().map(|(i, p)| {
+            let bytes: Box<dyn Iterator<Item = u8>> = Box::new(p.as_ref().iter().map(fold));
+            (i, bytes)
+        }));
+        let backward = Trie::build(patterns.iter().enumerate().map(|(i, p)| {
+            let bytes: Box<dyn Iterator<Item = u8>> = Box::new(p.as_ref().iter().rev().map(fold));
+            (i, bytes)
+        }));
+
+        AhoCorasick {
+            forward,
+            backward,
+            max_len: patterns.iter().map(|p| p.as_ref().len()).max().unwrap_or(0),
+            ascii_case_insensitive,
+        }
+    }
+
+    fn fold(&self, byte: u8) -> u8 {
+        if self.ascii_case_insensitive {
+            byte.to_ascii_lowercase()
+        } else {
+            byte
+        }
+    }
+
+    /// The match which starts first, and the longest of those if several start there
+    pub fn first_match(&self, haystack: &[u8]) -> Option<Match> {
+        let mut best: Option<Match> = None;
+        let mut state = 0;
+
+        for (i, &byte) in haystack.iter().enumerate() {
+            // nothing starting at or before the best start can end this late
+            if best.is_some_and(|b| i >= b.start + self.max_len) {
+                break;
+            }
+
+            state = self.forward.step(state, self.fold(byte));
+
+            // the longest match ending here is the one which starts earliest
+            if let Some((pattern, len)) = self.forward.outputs(state).next() {
+                let found = Match {
+                    pattern,
+                    start: i + 1 - len,
+                    end: i + 1,
+                };
+
+                if best.is_none_or(|b| found.start <= b.start) {
+                    best = Some(found);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The match which starts last, and the longest of those if several start there
+    pub fn last_match(&self, haystack: &[u8]) -> Option<Match> {
+        let mut state = 0;
+
+        // running the reversed patterns backwards, a match is found at the position where the
+        // original starts, so the first one found starts last
+        for (i, &byte) in haystack.iter().enumerate().rev() {
+            state = self.backward.step(state, self.fold(byte));
+
+            if let Some((pattern, len)) = self.backward.outputs(state).next() {
+                return Some(Match {
+                    pattern,
+                    start: i,
+                    end: i + len,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case_sensitive<P: AsRef<[u8]>>(patterns: &[P]) -> AhoCorasick {
+        AhoCorasick::build(patterns, false)
+    }
+
+    fn span(m: Option<Match>) -> Option<(usize, usize, usize)> {
+        m.map(|m| (m.pattern, m.start, m.end))
+    }
+
+    #[test]
+    fn overlapping() {
+        let ac = case_sensitive(&["he", "she", "his", "hers"]);
+        assert_eq!(span(ac.first_match(b"ushers")), Some((1, 1, 4)));
+        assert_eq!(span(ac.last_match(b"ushers")), Some((3, 2, 6)));
+
+        let ac = case_sensitive(&["eight", "two", "one"]);
+        assert_eq!(span(ac.first_match(b"xtwoneightwo")), Some((1, 1, 4)));
+        assert_eq!(span(ac.last_match(b"xtwoneightwo")), Some((1, 9, 12)));
+        assert_eq!(span(ac.last_match(b"xtwoneight")), Some((0, 5, 10)));
+    }
+
+    #[test]
+    fn first_and_last() {
+        let ac = case_sensitive(&["six", "sixteen", "teen", "x"]);
+
+        assert_eq!(span(ac.first_match(b"asixteenth")), Some((1, 1, 8)));
+        assert_eq!(span(ac.last_match(b"asixteenth")), Some((2, 4, 8)));
+
+        // "x" ends before "sixteen" does, but starts later
+        let first = ac.first_match(b"sixteen").unwrap();
+        assert_eq!(first.pattern, 1);
+
+        assert_eq!(ac.first_match(b"nothing"), None);
+        assert_eq!(ac.last_match(b""), None);
+    }
+
+    #[test]
+    fn case_folding() {
+        let ac = AhoCorasick::ascii_case_insensitive(&["Nine", "eight"]);
+        assert_eq!(span(ac.first_match(b"NINEighT")), Some((0, 0, 4)));
+        assert_eq!(span(ac.last_match(b"NINEighT")), Some((1, 3, 8)));
+
+        let ac = case_sensitive(&["nine"]);
+        assert_eq!(ac.first_match(b"NINE"), None);
+    }
+
+    #[test]
+    fn duplicates_and_bytes() {
+        let ac = case_sensitive(&["ab", "ab", "fünf"]);
+        assert_eq!(span(ac.last_match(b"xab")), Some((0, 1, 3)));
+
+        let haystack = "zwölffünf".as_bytes();
+        let found = ac.first_match(haystack).unwrap();
+        assert_eq!(&haystack[found.start..found.end], "fünf".as_bytes());
+    }
+
+    #[test]
+    fn many_patterns() {
+        // every number from 1 to 999 as digits; should all still come back right
+        let patterns: Vec<String> = (1..1000).map(|n| n.to_string()).collect();
+        let ac = case_sensitive(&patterns);
+
+        let haystack = b"abc907x";
+        assert_eq!(ac.first_match(haystack).map(|m| m.pattern + 1), Some(907));
+        assert_eq!(ac.last_match(haystack).map(|m| m.pattern + 1), Some(7));
+    }
+}
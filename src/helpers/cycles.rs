@@ -0,0 +1,94 @@
+//! Cycle detection for deterministic processes: start from some state and keep applying a step
+//! function; since the state space is finite, eventually it has to repeat. Everything here
+//! describes the result as `(offset, period)`, meaning the states from step `offset` onward
+//! repeat every `period` steps (and the first `offset` states are a "tail" which never recurs).
+
+/// Finds `(offset, period)` with Brent's algorithm, in constant memory
+pub fn find_cycle_brent<S, F>(start: S, mut step: F) -> (usize, usize)
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // first find the period: the hare runs ahead, and the tortoise teleports to the hare every
+    // time the search window doubles; once the cycle is shorter than the window, they meet
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // then the offset: start two walkers `period` apart, and they first meet at the cycle start
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+
+    let mut offset = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        offset += 1;
+    }
+
+    (offset, period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // some arbitrary but messy process
+    fn step(x: &u64) -> u64 {
+        (x * x + 1) % 1009
+    }
+
+    fn brute_force_state(start: u64, n: usize) -> u64 {
+        (0..n).fold(start, |x, _| step(&x))
+    }
+
+    #[test]
+    fn finds_tail_and_cycle() {
+        for start in [0, 3, 17, 400, 1008] {
+            let (offset, period) = find_cycle_brent(start, step);
+
+            assert_eq!(
+                brute_force_state(start, offset),
+                brute_force_state(start, offset + period),
+                "Start {start}"
+            );
+            if offset > 0 {
+                assert_ne!(
+                    brute_force_state(start, offset - 1),
+                    brute_force_state(start, offset - 1 + period)
+                );
+            }
+            for shorter in 1..period {
+                assert_ne!(
+                    brute_force_state(start, offset),
+                    brute_force_state(start, offset + shorter)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pure_cycle_and_fixed_point() {
+        // 0 -> 1 -> 2 -> 0 ...
+        assert_eq!(find_cycle_brent(0, |x| (x + 1) % 3), (0, 3));
+
+        // 5 -> 4 -> 3 -> 3 ...
+        let countdown = |x: &u32| if *x > 3 { x - 1 } else { *x };
+        assert_eq!(find_cycle_brent(5, countdown), (2, 1));
+    }
+}
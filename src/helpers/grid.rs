@@ -0,0 +1,290 @@
+//! Rectangular 2D grid, stored row-major. Positions are (x, y) with (0, 0) in the top left, so
+//! x is the column and y is the row, same as reading the input text.
+
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GridError {
+    /// No rows at all (or only empty rows)
+    Empty,
+    /// A row whose length doesn't match the first row
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// A character the cell parser didn't accept
+    BadChar { x: usize, y: usize, c: char },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "Grid is empty"),
+            GridError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has length {}, but expected {} (ragged grid)",
+                row, found, expected
+            ),
+            GridError::BadChar { x, y, c } => {
+                write!(f, "Unexpected character '{}' at ({}, {})", c, x, y)
+            }
+        }
+    }
+}
+
+const DIRS_4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+const DIRS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid out of rows, which must all be the same (nonzero) length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, GridError> {
+        let height = rows.len();
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+
+        if width == 0 {
+            return Err(GridError::Empty);
+        }
+
+        let mut cells = Vec::with_capacity(width * height);
+
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(GridError::Ragged {
+                    row: y,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Parses one cell per character, one row per line; `parse_cell` returns None for characters
+    /// which aren't allowed.
+    pub fn parse(
+        text: &str,
+        mut parse_cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, GridError> {
+        let mut rows = Vec::new();
+
+        for (y, line) in text.lines().enumerate() {
+            let mut row = Vec::with_capacity(line.len());
+
+            for (x, c) in line.chars().enumerate() {
+                row.push(parse_cell(c).ok_or(GridError::BadChar { x, y, c })?);
+            }
+
+            rows.push(row);
+        }
+
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        if self.in_bounds(pos) {
+            Some(&self.cells[pos.1 * self.width + pos.0])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            Some(&mut self.cells[pos.1 * self.width + pos.0])
+        } else {
+            None
+        }
+    }
+
+    /// Every position in the grid, in reading order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every (position, cell) pair, in reading order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The in-bounds position `(dx, dy)` away from `pos`, if there is one
+    pub fn step(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let pos = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        if self.in_bounds(pos) {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+
+    /// In-bounds orthogonal neighbours (up, right, down, left)
+    pub fn neighbours_4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRS_4.iter().filter_map(move |&d| self.step(pos, d))
+    }
+
+    /// In-bounds orthogonal and diagonal neighbours (clockwise, starting from the top left)
+    pub fn neighbours_8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRS_8.iter().filter_map(move |&d| self.step(pos, d))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks_exact(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column {} is out of bounds", x);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("Position {:?} is out of bounds", pos))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("Position {:?} is out of bounds", pos))
+    }
+}
+
+/// One row per line (no trailing newline), cells written back to back
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Grid<char> {
+        Grid::parse(text, Some).unwrap()
+    }
+
+    #[test]
+    fn parse_checks_shape() {
+        let grid = chars("abc\ndef");
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get((3, 0)), None);
+
+        assert_eq!(
+            Grid::parse("abc\nde", Some),
+            Err(GridError::Ragged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(Grid::parse("", Some), Err(GridError::Empty));
+        assert_eq!(
+            Grid::parse("..\n.x", |c| (c == '.').then_some(())),
+            Err(GridError::BadChar { x: 1, y: 1, c: 'x' })
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = chars("abc\ndef\nghi");
+
+        let n4: Vec<char> = grid.neighbours_4((1, 1)).map(|p| grid[p]).collect();
+        assert_eq!(n4, vec!['b', 'f', 'h', 'd']);
+
+        let n8: Vec<char> = grid.neighbours_8((1, 1)).map(|p| grid[p]).collect();
+        assert_eq!(n8, vec!['a', 'b', 'c', 'f', 'i', 'h', 'g', 'd']);
+
+        let corner: Vec<char> = grid.neighbours_8((0, 0)).map(|p| grid[p]).collect();
+        assert_eq!(corner, vec!['b', 'e', 'd']);
+    }
+
+    #[test]
+    fn views() {
+        let grid = chars("abc\ndef");
+
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(1).copied().collect::<String>(), "be");
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.map(|c| c.to_ascii_uppercase()).to_string(), "ABC\nDEF");
+    }
+}
@@ -0,0 +1,119 @@
+//! Half-open integer intervals (inclusive start, exclusive end, same as day 5) and sets of them.
+//! An `IntervalSet` keeps its intervals sorted, disjoint and non-touching, so two intervals like
+//! [1, 3) and [3, 5) are always stored merged as [1, 5).
+
+use std::ops::{Add, Sub};
+
+/// Anything that can be an interval endpoint; meant for the primitive integer types, where
+/// `Default` is zero.
+pub trait Bound: Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T> Bound for T where T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T> {}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Ord, PartialOrd)]
+pub struct Interval<T> {
+    // inclusive
+    pub start: T,
+    // exclusive
+    pub end: T,
+}
+
+impl<T: Bound> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Interval { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// The overlap of the two; may be empty
+    pub fn intersection(&self, other: &Self) -> Self {
+        Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct IntervalSet<T> {
+    // sorted, nonempty, and with a gap between each consecutive pair
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Bound> IntervalSet<T> {
+    /// The disjoint intervals making up the set, in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    /// Smallest value in the set
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+}
+
+impl<T: Bound> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut intervals: Vec<Interval<T>> = iter.into_iter().filter(|i| !i.is_empty()).collect();
+        intervals.sort();
+
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+        for next in intervals {
+            match merged.last_mut() {
+                Some(running) if next.start <= running.end => {
+                    running.end = running.end.max(next.end);
+                }
+                _ => merged.push(next),
+            }
+        }
+
+        IntervalSet { intervals: merged }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let a = Interval::new(0, 5);
+        let b = Interval::new(3, 8);
+
+        assert!(a.contains(0));
+        assert!(!a.contains(5));
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersection(&b), Interval::new(3, 5));
+
+        let c = Interval::new(5, 9);
+        assert!(!a.intersects(&c));
+        assert!(a.intersection(&c).is_empty());
+
+        let unsigned: Interval<u8> = Interval::new(250, 255);
+        assert!(unsigned.contains(254));
+    }
+
+    #[test]
+    fn collect_merges() {
+        let s: IntervalSet<i64> = [(5, 7), (1, 3), (3, 4), (10, 10), (6, 9)]
+            .into_iter()
+            .map(|(s, e)| Interval::new(s, e))
+            .collect();
+        let pairs: Vec<(i64, i64)> = s.iter().map(|i| (i.start, i.end)).collect();
+        assert_eq!(pairs, vec![(1, 4), (5, 9)]);
+        assert_eq!(s.min(), Some(1));
+
+        let empty: IntervalSet<u8> = [Interval::new(3, 3)].into_iter().collect();
+        assert_eq!(empty.min(), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Just enough JSON writing for hand-built output, since nothing here needs a full serializer.
+
+/// The string as a JSON string literal, quotes included
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+/// A JSON object with the given (already encoded) values, in order
+pub fn json_object<'a>(fields: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), value))
+        .collect();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+/// A JSON array of the given (already encoded) values
+pub fn json_array(values: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("plain"), r#""plain""#);
+        assert_eq!(json_string("a \"b\"\n\\"), r#""a \"b\"\n\\""#);
+        assert_eq!(json_string("\u{1}"), r#""\u0001""#);
+    }
+
+    #[test]
+    fn building() {
+        let object = json_object([
+            ("id", "1".to_string()),
+            ("names", json_array(["x", "y"].map(json_string))),
+            ("none", json_array([])),
+        ]);
+        assert_eq!(object, r#"{"id":1,"names":["x","y"],"none":[]}"#);
+    }
+}
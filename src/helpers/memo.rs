@@ -0,0 +1,62 @@
+//! Memoization for recursive solvers. The cache is passed back into the computation, so a
+//! recursive function just wraps its body in `get_or_compute` and recurses through the cache it's
+//! handed. Keys should be small owned state (indices into the input, counters, and so on) rather
+//! than borrowed slices, so they're cheap to hash and don't tie the cache to a lifetime.
+
+use std::hash::Hash;
+
+use ahash::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo {
+            cache: HashMap::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached value for the key, or else the result of `compute`, which gets the cache back
+    /// so it can recurse through it
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u128>, n: u64) -> u128 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n as u128
+            } else {
+                fib(memo, n - 1) + fib(memo, n - 2)
+            }
+        })
+    }
+
+    #[test]
+    fn remembers() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(&mut memo, 150), 9969216677189303386214405760200);
+        assert_eq!(memo.cache.len(), 151);
+    }
+}
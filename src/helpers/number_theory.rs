@@ -0,0 +1,273 @@
+//! Integer number theory: extended Euclid, and the Chinese remainder theorem for
+//! moduli which don't have to be coprime. All arithmetic is checked; intermediate products are
+//! widened, and a result which doesn't fit in an i128 comes back as an error instead of silently
+//! wrapping, and `crt_big` is there for when that happens.
+
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+use num_integer::{ExtendedGcd, Integer};
+
+/// Returns `(g, x, y)` where `g = gcd(a, b)` and `a * x + b * y = g`; None on overflow
+pub fn extended_gcd(a: i128, b: i128) -> Option<(i128, i128, i128)> {
+    // one step of the remainder sequence: (old, new) -> (new, old - q * new)
+    let step = |old: i128, new: i128, q: i128| -> Option<(i128, i128)> {
+        Some((new, old.checked_sub(q.checked_mul(new)?)?))
+    };
+
+    // invariant: old_r = a * old_s + b * old_t, and likewise for r, s, t
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let q = old_r.checked_div(r)?;
+        (old_r, r) = step(old_r, r, q)?;
+        (old_s, s) = step(old_s, s, q)?;
+        (old_t, t) = step(old_t, t, q)?;
+    }
+
+    if old_r < 0 {
+        Some((
+            old_r.checked_neg()?,
+            old_s.checked_neg()?,
+            old_t.checked_neg()?,
+        ))
+    } else {
+        Some((old_r, old_s, old_t))
+    }
+}
+
+/// `(a * b) mod m`, in `0..m`; m has to be positive. The product is widened to a `BigInt` when
+/// it doesn't fit in an i128, so this can't overflow.
+fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    match a.checked_mul(b) {
+        Some(p) => p.rem_euclid(m),
+        None => {
+            let p = (BigInt::from(a) * BigInt::from(b)).mod_floor(&BigInt::from(m));
+            i128::try_from(p).expect("Anything reduced mod an i128 should fit in an i128")
+        }
+    }
+}
+
+/// The statement "x = residue (mod modulus)"; residue is always kept in `0..modulus`
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Congruence {
+    pub residue: i128,
+    pub modulus: i128,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum CrtError {
+    /// Moduli have to be positive
+    BadModulus(i128),
+    /// No x satisfies both congruences at once
+    Inconsistent(Congruence, Congruence),
+    /// The combined modulus (or some intermediate value) doesn't fit in an i128
+    Overflow,
+}
+
+impl Display for CrtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CrtError::BadModulus(m) => write!(f, "Modulus {} should be positive", m),
+            CrtError::Inconsistent(a, b) => write!(
+                f,
+                "x = {} (mod {}) and x = {} (mod {}) have no common solution",
+                a.residue, a.modulus, b.residue, b.modulus
+            ),
+            CrtError::Overflow => write!(f, "Overflow while combining congruences"),
+        }
+    }
+}
+
+impl Congruence {
+    pub fn new(residue: i128, modulus: i128) -> Result<Self, CrtError> {
+        if modulus <= 0 {
+            return Err(CrtError::BadModulus(modulus));
+        }
+
+        Ok(Congruence {
+            residue: residue.rem_euclid(modulus),
+            modulus,
+        })
+    }
+
+    /// Combines the two into a single congruence (mod the lcm of the moduli) which holds exactly
+    /// when both of them do
+    pub fn merge(self, other: Congruence) -> Result<Congruence, CrtError> {
+        let (a1, m1) = (self.residue, self.modulus);
+        let (a2, m2) = (other.residue, other.modulus);
+
+        let (g, inv, _) = extended_gcd(m1, m2).ok_or(CrtError::Overflow)?;
+
+        // x = a1 + m1 * t, so need m1 * t = a2 - a1 (mod m2), which needs g | a2 - a1
+        let diff = a2 - a1;
+        if diff % g != 0 {
+            return Err(CrtError::Inconsistent(self, other));
+        }
+
+        let reduced_m2 = m2 / g;
+        let modulus = m1.checked_mul(reduced_m2).ok_or(CrtError::Overflow)?;
+
+        // inv is the inverse of m1 / g, mod m2 / g; both factors are reduced first so t is
+        // in 0..reduced_m2, and then m1 * t + a1 is less than m1 * reduced_m2 = modulus
+        let t = mul_mod(
+            (diff / g).rem_euclid(reduced_m2),
+            inv.rem_euclid(reduced_m2),
+            reduced_m2,
+        );
+        let residue = m1
+            .checked_mul(t)
+            .and_then(|x| x.checked_add(a1))
+            .ok_or(CrtError::Overflow)?;
+
+        Congruence::new(residue, modulus)
+    }
+}
+
+/// Merges all the congruences into one; the empty list is "x = 0 (mod 1)", i.e. anything goes
+pub fn crt(congruences: &[Congruence]) -> Result<Congruence, CrtError> {
+    congruences
+        .iter()
+        .try_fold(Congruence::new(0, 1)?, |acc, c| acc.merge(*c))
+}
+
+/// Like `Congruence`, but with no limit on size
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct BigCongruence {
+    pub residue: BigInt,
+    pub modulus: BigInt,
+}
+
+/// Like `crt`, but combines everything as `BigInt`s, so it can't overflow; None if the
+/// congruences have no common solution
+pub fn crt_big(congruences: &[Congruence]) -> Option<BigCongruence> {
+    let mut residue = BigInt::from(0);
+    let mut modulus = BigInt::from(1);
+
+    for c in congruences {
+        let (a2, m2) = (BigInt::from(c.residue), BigInt::from(c.modulus));
+
+        // same as Congruence::merge
+        let ExtendedGcd { gcd: g, x: inv, .. } = modulus.extended_gcd(&m2);
+
+        let diff = a2 - &residue;
+        if !diff.is_multiple_of(&g) {
+            return None;
+        }
+
+        let reduced_m2 = m2 / &g;
+        let t = ((diff / &g) * inv).mod_floor(&reduced_m2);
+
+        residue += &modulus * t;
+        modulus *= reduced_m2;
+        residue = residue.mod_floor(&modulus);
+    }
+
+    Some(BigCongruence { residue, modulus })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cong(residue: i128, modulus: i128) -> Congruence {
+        Congruence::new(residue, modulus).unwrap()
+    }
+
+    #[test]
+    fn bezout() {
+        for (a, b, g) in [
+            (240, 46, 2),
+            (-7, 3, 1),
+            (0, 9, 9),
+            (17, 0, 17),
+            (-12, 18, 6),
+        ] {
+            let (found, x, y) = extended_gcd(a, b).unwrap();
+            assert_eq!(found, g, "a={a}, b={b}");
+            assert_eq!(a * x + b * y, g, "a={a}, b={b}");
+        }
+    }
+
+    #[test]
+    fn extreme_inputs() {
+        // neither i128::MIN / -1 nor |i128::MIN| fits in an i128
+        assert_eq!(extended_gcd(i128::MIN, -1), None);
+        assert_eq!(extended_gcd(i128::MIN, 0), None);
+        let (g, x, y) = extended_gcd(i128::MAX, i128::MAX - 1).unwrap();
+        assert_eq!(g, 1);
+        assert_eq!(
+            BigInt::from(i128::MAX) * x + BigInt::from(i128::MAX - 1) * y,
+            BigInt::from(1)
+        );
+    }
+
+    #[test]
+    fn coprime_crt() {
+        let soln = crt(&[cong(2, 3), cong(3, 5), cong(2, 7)]).unwrap();
+        assert_eq!(soln, cong(23, 105));
+    }
+
+    #[test]
+    fn non_coprime_crt() {
+        // x = 3 (mod 4) and x = 5 (mod 6) is x = 11 (mod 12)
+        assert_eq!(cong(3, 4).merge(cong(5, 6)), Ok(cong(11, 12)));
+
+        // x = 1 (mod 4) and x = 2 (mod 6) can't both hold (parity)
+        assert_eq!(
+            cong(1, 4).merge(cong(2, 6)),
+            Err(CrtError::Inconsistent(cong(1, 4), cong(2, 6)))
+        );
+
+        // same modulus twice is fine as long as they agree
+        assert_eq!(cong(5, 9).merge(cong(14, 9)), Ok(cong(5, 9)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Congruence::new(1, 0), Err(CrtError::BadModulus(0)));
+
+        let big = (1_i128 << 100) + 1;
+        let other = (1_i128 << 100) - 1;
+        assert_eq!(cong(1, big).merge(cong(2, other)), Err(CrtError::Overflow));
+    }
+
+    #[test]
+    fn big_intermediates() {
+        // the combined modulus fits easily, but diff * inv is around 2^140
+        let m2 = (1_i128 << 70) + 1;
+        let merged = cong(2, 3).merge(cong(m2 - 5, m2)).unwrap();
+        assert_eq!(merged.modulus, 3 * m2);
+        assert_eq!(merged.residue % 3, 2);
+        assert_eq!(merged.residue % m2, m2 - 5);
+
+        // and agrees with the slow version
+        let congruences = [cong(2, 3), cong(m2 - 5, m2), cong(4, 7)];
+        let small = crt(&congruences).unwrap();
+        let big = crt_big(&congruences).unwrap();
+        assert_eq!(BigInt::from(small.residue), big.residue);
+        assert_eq!(BigInt::from(small.modulus), big.modulus);
+    }
+
+    #[test]
+    fn big_crt() {
+        // agrees with the checked version when that works
+        let congruences = [cong(3, 4), cong(5, 6), cong(2, 7)];
+        let small = crt(&congruences).unwrap();
+        let big = crt_big(&congruences).unwrap();
+        assert_eq!(big.residue, BigInt::from(small.residue));
+        assert_eq!(big.modulus, BigInt::from(small.modulus));
+
+        assert_eq!(crt_big(&[cong(1, 4), cong(2, 6)]), None);
+
+        // and keeps going when it doesn't
+        let big = (1_i128 << 100) + 1;
+        let other = (1_i128 << 100) - 1;
+        let soln = crt_big(&[cong(1, big), cong(2, other)]).unwrap();
+        assert_eq!(soln.modulus, BigInt::from(big) * BigInt::from(other));
+        assert_eq!(&soln.residue % BigInt::from(big), BigInt::from(1));
+        assert_eq!(&soln.residue % BigInt::from(other), BigInt::from(2));
+    }
+}
@@ -0,0 +1,216 @@
+//! Common nom parsers, so the days don't each need their own "digits, then unwrap the parse" and
+//! so on. Everything here uses `ParseError` as the nom error type, which is also what
+//! `parse_all` hands back when the input doesn't parse.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{char, digit1, line_ending, multispace0, one_of, space1};
+use nom::combinator::{eof, opt, recognize};
+use nom::error::{ContextError, ErrorKind, FromExternalError};
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, tuple};
+use nom::IResult;
+
+pub type PResult<'a, T> = IResult<&'a str, T, ParseError<'a>>;
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ParseErrorKind {
+    /// One of nom's own parsers failed
+    Nom(ErrorKind),
+    /// Expected a specific character
+    Char(char),
+    /// A number was well-formed, but didn't fit in the target type
+    Overflow,
+    /// Ran out of input (only possible with streaming parsers)
+    Incomplete,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ParseError<'a> {
+    /// The remaining input at the point where parsing failed
+    pub input: &'a str,
+    pub kind: ParseErrorKind,
+    /// Labels added with nom's `context`, innermost first
+    pub context: Vec<&'static str>,
+}
+
+impl<'a> ParseError<'a> {
+    pub fn new(input: &'a str, kind: ParseErrorKind) -> Self {
+        ParseError {
+            input,
+            kind,
+            context: Vec::new(),
+        }
+    }
+}
+
+impl<'a> nom::error::ParseError<&'a str> for ParseError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        ParseError::new(input, ParseErrorKind::Nom(kind))
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        // the innermost error is the most useful one
+        other
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        ParseError::new(input, ParseErrorKind::Char(c))
+    }
+}
+
+impl<'a> ContextError<&'a str> for ParseError<'a> {
+    fn add_context(_input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        other.context.push(ctx);
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for ParseError<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, _e: E) -> Self {
+        ParseError::new(input, ParseErrorKind::Nom(kind))
+    }
+}
+
+impl Display for ParseError<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Nom(kind) => write!(f, "Parse error ({})", kind.description())?,
+            ParseErrorKind::Char(c) => write!(f, "Expected '{}'", c)?,
+            ParseErrorKind::Overflow => write!(f, "Number too large")?,
+            ParseErrorKind::Incomplete => write!(f, "Unexpected end of input")?,
+        }
+
+        for ctx in self.context.iter().rev() {
+            write!(f, " in {}", ctx)?;
+        }
+
+        let snippet = self.input.lines().next().unwrap_or("");
+        write!(f, " at '{}'", snippet)
+    }
+}
+
+fn parse_number<'a, T: FromStr>(input: &'a str, text: &'a str, rest: &'a str) -> PResult<'a, T> {
+    match text.parse::<T>() {
+        Ok(n) => Ok((rest, n)),
+        Err(_) => Err(nom::Err::Failure(ParseError::new(
+            input,
+            ParseErrorKind::Overflow,
+        ))),
+    }
+}
+
+/// A run of digits, as any integer type; fails (rather than panicking or wrapping) if the number
+/// doesn't fit
+pub fn unsigned<'a, T: FromStr>(input: &'a str) -> PResult<'a, T> {
+    let (rest, digits) = digit1(input)?;
+    parse_number(input, digits, rest)
+}
+
+/// Like `unsigned`, but allows a leading sign
+pub fn signed<'a, T: FromStr>(input: &'a str) -> PResult<'a, T> {
+    let (rest, text) = recognize(pair(opt(one_of("+-")), digit1))(input)?;
+    parse_number(input, text, rest)
+}
+
+/// One or more of the thing, separated by (any amount of) spaces or tabs
+pub fn spaced<'a, O>(
+    parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    separated_list1(space1, parser)
+}
+
+/// One or more of the thing, one per line
+pub fn lines<'a, O>(
+    parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    separated_list1(line_ending, parser)
+}
+
+/// An empty line (so, two line endings in a row)
+pub fn blank_line(input: &str) -> PResult<'_, ()> {
+    let (input, _) = pair(line_ending, line_ending)(input)?;
+    Ok((input, ()))
+}
+
+/// One or more of the thing, separated by blank lines
+pub fn blocks<'a, O>(
+    parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, Vec<O>> {
+    separated_list1(blank_line, parser)
+}
+
+/// Something like "Time:  7 15 30"; the label, a colon, some spaces, then the thing
+pub fn labelled<'a, O>(
+    label: &'static str,
+    parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> impl FnMut(&'a str) -> PResult<'a, O> {
+    preceded(tuple((tag(label), char(':'), space1)), parser)
+}
+
+/// Runs the parser on the whole input; anything left over (other than trailing whitespace) is an
+/// error
+pub fn parse_all<'a, O>(
+    input: &'a str,
+    mut parser: impl FnMut(&'a str) -> PResult<'a, O>,
+) -> Result<O, ParseError<'a>> {
+    let flatten = |e: nom::Err<ParseError<'a>>| match e {
+        nom::Err::Error(e) | nom::Err::Failure(e) => e,
+        nom::Err::Incomplete(_) => ParseError::new("", ParseErrorKind::Incomplete),
+    };
+
+    let (rest, out) = parser(input).map_err(flatten)?;
+    let (_, _) = pair(multispace0, eof)(rest).map_err(flatten)?;
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(unsigned::<u64>("123 abc"), Ok((" abc", 123)));
+        assert_eq!(signed::<i64>("-12,"), Ok((",", -12)));
+        assert_eq!(signed::<i64>("+7"), Ok(("", 7)));
+        assert!(unsigned::<u64>("-12").is_err());
+
+        assert_eq!(
+            unsigned::<u8>("300"),
+            Err(nom::Err::Failure(ParseError::new(
+                "300",
+                ParseErrorKind::Overflow
+            )))
+        );
+    }
+
+    #[test]
+    fn lists_and_headers() {
+        let mut parser = labelled("Time", spaced(unsigned::<u32>));
+        assert_eq!(parser("Time:   7  15\t30"), Ok(("", vec![7, 15, 30])));
+        assert!(parser("Distance: 7").is_err());
+    }
+
+    #[test]
+    fn blocks_of_lines() {
+        let input = "1 2\n3\n\n4\n";
+        let parsed = parse_all(input, blocks(lines(spaced(unsigned::<u8>))));
+
+        assert_eq!(parsed, Ok(vec![vec![vec![1, 2], vec![3]], vec![vec![4]]]));
+    }
+
+    #[test]
+    fn parse_all_requires_everything() {
+        assert_eq!(parse_all("12\n", unsigned::<u8>), Ok(12));
+
+        let err = parse_all("12 x", unsigned::<u8>).unwrap_err();
+        assert_eq!(err.input, "x");
+        assert_eq!(err.to_string(), "Parse error (End of file) at 'x'");
+
+        let err = parse_all("999", unsigned::<u8>).unwrap_err();
+        assert_eq!(err.to_string(), "Number too large at '999'");
+    }
+}
@@ -0,0 +1,208 @@
+//! Piecewise maps on the integers, where each piece just shifts its values by a constant (and
+//! anything not covered by a piece maps to itself). These are exactly day 5's almanac maps, but
+//! here they can also be composed, so a whole chain of them collapses into one.
+
+use crate::helpers::intervals::{Interval, IntervalSet};
+
+/// Every x in `domain` maps to `x + offset`
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Shift {
+    pub domain: Interval<i64>,
+    pub offset: i64,
+}
+
+impl Shift {
+    fn image(&self) -> Interval<i64> {
+        Interval::new(
+            self.domain.start + self.offset,
+            self.domain.end + self.offset,
+        )
+    }
+}
+
+/// Two of the shifts passed to `PiecewiseMap::from_shifts` claim the same values
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct OverlapError(pub Interval<i64>, pub Interval<i64>);
+
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct PiecewiseMap {
+    // sorted and disjoint; never empty or zero-offset, and neighbours that touch always have
+    // different offsets, so equal maps always have equal representations
+    shifts: Vec<Shift>,
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        Self::default()
+    }
+
+    pub fn from_shifts(shifts: impl IntoIterator<Item = Shift>) -> Result<Self, OverlapError> {
+        let mut shifts: Vec<Shift> = shifts
+            .into_iter()
+            .filter(|s| !s.domain.is_empty())
+            .collect();
+        shifts.sort_by_key(|s| s.domain);
+
+        for pair in shifts.windows(2) {
+            if pair[0].domain.intersects(&pair[1].domain) {
+                return Err(OverlapError(pair[0].domain, pair[1].domain));
+            }
+        }
+
+        Ok(Self::normalized(shifts))
+    }
+
+    // PRE: sorted and disjoint
+    fn normalized(shifts: Vec<Shift>) -> Self {
+        let mut out: Vec<Shift> = Vec::with_capacity(shifts.len());
+
+        for shift in shifts {
+            if shift.domain.is_empty() || shift.offset == 0 {
+                continue;
+            }
+
+            match out.last_mut() {
+                Some(last)
+                    if last.domain.end == shift.domain.start && last.offset == shift.offset =>
+                {
+                    last.domain.end = shift.domain.end;
+                }
+                _ => out.push(shift),
+            }
+        }
+
+        PiecewiseMap { shifts: out }
+    }
+
+    /// The shifts, plus identity pieces filling in the gaps, so together they cover every i64
+    /// (well, everything but i64::MAX)
+    fn segments(&self) -> Vec<Shift> {
+        let mut out = Vec::with_capacity(self.shifts.len() * 2 + 1);
+        let mut covered_to = i64::MIN;
+
+        for shift in self.shifts.iter().copied() {
+            if covered_to < shift.domain.start {
+                out.push(Shift {
+                    domain: Interval::new(covered_to, shift.domain.start),
+                    offset: 0,
+                });
+            }
+            out.push(shift);
+            covered_to = shift.domain.end;
+        }
+
+        if covered_to < i64::MAX {
+            out.push(Shift {
+                domain: Interval::new(covered_to, i64::MAX),
+                offset: 0,
+            });
+        }
+
+        out
+    }
+
+    pub fn apply(&self, x: i64) -> i64 {
+        let ind = self.shifts.partition_point(|s| s.domain.end <= x);
+        match self.shifts.get(ind) {
+            Some(shift) if shift.domain.contains(x) => x + shift.offset,
+            _ => x,
+        }
+    }
+
+    /// Everything the interval maps to
+    pub fn apply_interval(&self, interval: Interval<i64>) -> IntervalSet<i64> {
+        self.segments()
+            .into_iter()
+            .map(|s| {
+                let part = s.domain.intersection(&interval);
+                Interval::new(part.start + s.offset, part.end + s.offset)
+            })
+            .collect()
+    }
+
+    /// The map which does `self`, then `next`
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let next_segments = next.segments();
+
+        let mut out = Vec::new();
+
+        for seg in self.segments() {
+            let image = seg.image();
+
+            let first = next_segments.partition_point(|t| t.domain.end <= image.start);
+            for t in next_segments[first..]
+                .iter()
+                .take_while(|t| t.domain.start < image.end)
+            {
+                let overlap = image.intersection(&t.domain);
+                out.push(Shift {
+                    domain: Interval::new(overlap.start - seg.offset, overlap.end - seg.offset),
+                    offset: seg.offset + t.offset,
+                });
+            }
+        }
+
+        Self::normalized(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift(start: i64, end: i64, offset: i64) -> Shift {
+        Shift {
+            domain: Interval::new(start, end),
+            offset,
+        }
+    }
+
+    // day 5's first sample map: 98..100 -> 50..52, 50..98 -> 52..100
+    fn sample() -> PiecewiseMap {
+        PiecewiseMap::from_shifts([shift(98, 100, -48), shift(50, 98, 2)]).unwrap()
+    }
+
+    #[test]
+    fn apply() {
+        let m = sample();
+        assert_eq!(m.apply(49), 49);
+        assert_eq!(m.apply(50), 52);
+        assert_eq!(m.apply(97), 99);
+        assert_eq!(m.apply(98), 50);
+        assert_eq!(m.apply(100), 100);
+
+        let image = m.apply_interval(Interval::new(90, 110));
+        let image: Vec<Interval<i64>> = image.iter().collect();
+        assert_eq!(image, vec![Interval::new(50, 52), Interval::new(92, 110)]);
+    }
+
+    #[test]
+    fn overlaps_rejected() {
+        assert_eq!(
+            PiecewiseMap::from_shifts([shift(0, 10, 1), shift(5, 15, 2)]),
+            Err(OverlapError(Interval::new(0, 10), Interval::new(5, 15)))
+        );
+    }
+
+    #[test]
+    fn normalizes() {
+        let m =
+            PiecewiseMap::from_shifts([shift(0, 5, 3), shift(5, 10, 3), shift(20, 30, 0)]).unwrap();
+        assert_eq!(m, PiecewiseMap::from_shifts([shift(0, 10, 3)]).unwrap());
+    }
+
+    #[test]
+    fn composition() {
+        let first = sample();
+        let second = PiecewiseMap::from_shifts([shift(0, 60, 1000), shift(95, 101, -90)]).unwrap();
+
+        let composed = first.then(&second);
+
+        for x in -5..120 {
+            assert_eq!(composed.apply(x), second.apply(first.apply(x)), "x={x}");
+        }
+
+        assert_eq!(first.then(&PiecewiseMap::identity()), first);
+        assert_eq!(PiecewiseMap::identity().then(&first), first);
+    }
+}
@@ -0,0 +1,97 @@
+//! Sequences which come from polynomials: extrapolation from finite differences to any index at
+//! all, not just one step past either end. The samples are always at x = 0, 1, 2, ...
+
+use num_bigint::BigInt;
+
+use crate::helpers::wide::Wide;
+
+/// A sequence in Newton's forward difference form: just the first entry of each row of the
+/// difference table, which is enough to recover the value at any index
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Newton {
+    // leading[k] is the k-th difference at x = 0; trailing zero rows are dropped
+    leading: Vec<Wide>,
+}
+
+impl Newton {
+    pub fn from_values(values: &[i64]) -> Self {
+        // difference in place, so the whole table only needs the one buffer; the differences can
+        // grow exponentially with the length, so this is all in `Wide`
+        let mut row: Vec<Wide> = values.iter().map(|&v| Wide::from(v)).collect();
+        let mut leading = Vec::with_capacity(row.len());
+
+        while !row.is_empty() {
+            if row.iter().all(|v| *v == Wide::default()) {
+                break;
+            }
+
+            leading.push(row[0].clone());
+
+            for i in 0..row.len() - 1 {
+                row[i] = row[i + 1].clone() - std::mem::take(&mut row[i]);
+            }
+            row.pop();
+        }
+
+        Newton { leading }
+    }
+
+    /// The value at index x, which may be anywhere (including negative); this is the sum of
+    /// binomial(x, k) times the k-th leading difference
+    pub fn value_at(&self, x: i64) -> Wide {
+        let x = BigInt::from(x);
+
+        let mut total = BigInt::from(0);
+        let mut binomial = BigInt::from(1);
+
+        for (k, diff) in self.leading.iter().enumerate() {
+            total += &binomial * diff.to_big();
+
+            // binomial(x, k + 1) = binomial(x, k) * (x - k) / (k + 1), and the division is exact
+            binomial = binomial * (&x - k) / (k + 1);
+        }
+
+        Wide::from(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolation() {
+        // triangle numbers: x = 0 is 1, so the value at x is (x + 1)(x + 2) / 2
+        let newton = Newton::from_values(&[1, 3, 6, 10, 15, 21]);
+        for x in [-10, -2, -1, 0, 3, 6, 100, 1_000_000_000] {
+            let x128 = x as i128;
+            assert_eq!(
+                newton.value_at(x),
+                Wide::from((x128 + 1) * (x128 + 2) / 2),
+                "x={x}"
+            );
+        }
+
+        // cubes, well past anything an i64 could hold
+        let cubes = Newton::from_values(&[0, 1, 8, 27, 64]);
+        let x = i64::MAX;
+        assert_eq!(
+            cubes.value_at(x),
+            Wide::from(x) * Wide::from(x) * Wide::from(x)
+        );
+    }
+
+    #[test]
+    fn huge_differences() {
+        // alternating signs double the differences every row, so the last ones are around 2^132
+        let values: Vec<i64> = (0..70)
+            .map(|i| if i % 2 == 0 { 1 << 62 } else { -(1 << 62) })
+            .collect();
+        let newton = Newton::from_values(&values);
+
+        for (x, &v) in values.iter().enumerate() {
+            assert_eq!(newton.value_at(x as i64), Wide::from(v), "x={x}");
+        }
+        assert!(matches!(newton.value_at(70), Wide::Big(_)));
+    }
+}
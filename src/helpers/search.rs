@@ -0,0 +1,189 @@
+//! Graph searches over implicit graphs: nothing here needs the graph built up front, just a
+//! closure giving each node's neighbours.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use ahash::{HashMap, HashSet};
+
+/// Whether a search should remember how it reached each node (costs a map entry per node)
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Paths {
+    Track,
+    Skip,
+}
+
+/// What a search found: the cost of every node it reached, and (if asked for) where each one was
+/// reached from
+#[derive(Clone, Debug)]
+pub struct Reached<N, C> {
+    pub costs: HashMap<N, C>,
+    predecessors: Option<HashMap<N, N>>,
+}
+
+impl<N: Clone + Eq + Hash, C> Reached<N, C> {
+    fn new(paths: Paths) -> Self {
+        Reached {
+            costs: HashMap::default(),
+            predecessors: match paths {
+                Paths::Track => Some(HashMap::default()),
+                Paths::Skip => None,
+            },
+        }
+    }
+
+    fn set_predecessor(&mut self, node: &N, prev: &N) {
+        if let Some(preds) = self.predecessors.as_mut() {
+            preds.insert(node.clone(), prev.clone());
+        }
+    }
+
+    /// The path from a start node to the target, inclusive of both ends; None if the target wasn't
+    /// reached or paths weren't tracked
+    pub fn path_to(&self, target: &N) -> Option<Vec<N>> {
+        let preds = self.predecessors.as_ref()?;
+        if !self.costs.contains_key(target) {
+            return None;
+        }
+
+        let mut path = vec![target.clone()];
+        while let Some(prev) = preds.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Breadth-first search from all the starts at once; the cost of a node is the number of steps
+/// to the nearest start
+pub fn bfs<N, I, F>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: F,
+    paths: Paths,
+) -> Reached<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    let mut reached = Reached::new(paths);
+    let mut to_process = VecDeque::new();
+
+    for start in starts {
+        if !reached.costs.contains_key(&start) {
+            reached.costs.insert(start.clone(), 0);
+            to_process.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, cost)) = to_process.pop_front() {
+        for next in neighbours(&node) {
+            if reached.costs.contains_key(&next) {
+                continue;
+            }
+
+            reached.costs.insert(next.clone(), cost + 1);
+            reached.set_predecessor(&next, &node);
+            to_process.push_back((next, cost + 1));
+        }
+    }
+
+    reached
+}
+
+/// Everything reachable from any of the starts (including the starts themselves); cheaper than
+/// `bfs` when the distances don't matter
+pub fn flood_fill<N, I, F>(starts: impl IntoIterator<Item = N>, mut neighbours: F) -> HashSet<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+{
+    let mut seen = HashSet::default();
+    let mut to_process: Vec<N> = starts.into_iter().collect();
+
+    while let Some(node) = to_process.pop() {
+        if seen.contains(&node) {
+            continue;
+        }
+
+        to_process.extend(neighbours(&node).into_iter().filter(|n| !seen.contains(n)));
+        seen.insert(node);
+    }
+
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a little maze with a loop in it, one side 4 steps longer than the other; '#' is wall
+    const MAZE: &str = "\
+#.#####
+#.....#
+#.###.#
+#.#...#
+#.#.###
+#.....#
+#####.#";
+
+    fn open_cells(maze: &str) -> HashSet<(i32, i32)> {
+        maze.lines()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|&(_, c)| c == '.')
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect()
+    }
+
+    fn steps(open: &HashSet<(i32, i32)>) -> impl FnMut(&(i32, i32)) -> Vec<(i32, i32)> + '_ {
+        |&(x, y)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|p| open.contains(p))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn bfs_and_paths() {
+        let open = open_cells(MAZE);
+        let reached = bfs([(1, 0)], steps(&open), Paths::Track);
+
+        assert_eq!(reached.costs[&(5, 6)], 10);
+        assert_eq!(reached.costs.len(), open.len());
+
+        let path = reached.path_to(&(5, 6)).unwrap();
+        assert_eq!(path.len(), 11);
+        assert_eq!(path.first(), Some(&(1, 0)));
+        assert_eq!(path.last(), Some(&(5, 6)));
+
+        let untracked = bfs([(1, 0)], steps(&open), Paths::Skip);
+        assert_eq!(untracked.costs, reached.costs);
+        assert_eq!(untracked.path_to(&(5, 6)), None);
+    }
+
+    #[test]
+    fn flood_fill_multiple_starts() {
+        let filled = flood_fill(
+            [0, 10],
+            |&n: &i32| {
+                if n % 10 < 3 {
+                    vec![n + 1]
+                } else {
+                    vec![]
+                }
+            },
+        );
+
+        let mut filled: Vec<i32> = filled.into_iter().collect();
+        filled.sort();
+        assert_eq!(filled, vec![0, 1, 2, 3, 10, 11, 12, 13]);
+    }
+}
@@ -0,0 +1,205 @@
+//! Integers which are machine-sized while they fit, and arbitrary-precision once they don't, for
+//! answers which could overflow on big (or deliberately nasty) inputs. Every operation is checked:
+//! anything that would overflow an i128 is redone with `BigInt`s instead of wrapping or panicking.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Wide {
+    Small(i128),
+    /// Only ever holds values which don't fit in an i128, so every value has one representation
+    Big(BigInt),
+}
+
+impl Wide {
+    fn from_big(big: BigInt) -> Self {
+        match big.to_i128() {
+            Some(small) => Wide::Small(small),
+            None => Wide::Big(big),
+        }
+    }
+
+    pub fn to_big(&self) -> BigInt {
+        match self {
+            Wide::Small(n) => BigInt::from(*n),
+            Wide::Big(n) => n.clone(),
+        }
+    }
+
+    /// 2 to the given power
+    pub fn pow2(exp: u32) -> Self {
+        if exp < 127 {
+            Wide::Small(1 << exp)
+        } else {
+            Wide::Big(BigInt::from(1) << exp)
+        }
+    }
+}
+
+impl Default for Wide {
+    fn default() -> Self {
+        Wide::Small(0)
+    }
+}
+
+macro_rules! from_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Wide {
+                fn from(n: $t) -> Self {
+                    match i128::try_from(n) {
+                        Ok(small) => Wide::Small(small),
+                        Err(_) => Wide::Big(BigInt::from(n)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl From<BigInt> for Wide {
+    fn from(n: BigInt) -> Self {
+        Wide::from_big(n)
+    }
+}
+
+macro_rules! checked_op {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl $trait for Wide {
+            type Output = Wide;
+
+            fn $method(self, rhs: Wide) -> Wide {
+                if let (Wide::Small(a), Wide::Small(b)) = (&self, &rhs) {
+                    if let Some(out) = a.$checked(*b) {
+                        return Wide::Small(out);
+                    }
+                }
+
+                Wide::from_big(self.to_big().$method(rhs.to_big()))
+            }
+        }
+    };
+}
+
+checked_op!(Add, add, checked_add);
+checked_op!(Sub, sub, checked_sub);
+checked_op!(Mul, mul, checked_mul);
+
+impl AddAssign for Wide {
+    fn add_assign(&mut self, rhs: Wide) {
+        *self = std::mem::take(self) + rhs;
+    }
+}
+
+impl MulAssign for Wide {
+    fn mul_assign(&mut self, rhs: Wide) {
+        *self = std::mem::take(self) * rhs;
+    }
+}
+
+impl Sum for Wide {
+    fn sum<I: Iterator<Item = Wide>>(iter: I) -> Self {
+        iter.fold(Wide::default(), |acc, n| acc + n)
+    }
+}
+
+impl<'a> Sum<&'a Wide> for Wide {
+    fn sum<I: Iterator<Item = &'a Wide>>(iter: I) -> Self {
+        iter.fold(Wide::default(), |acc, n| acc + n.clone())
+    }
+}
+
+impl PartialOrd for Wide {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Wide {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Wide::Small(a), Wide::Small(b)) => a.cmp(b),
+            _ => self.to_big().cmp(&other.to_big()),
+        }
+    }
+}
+
+impl Display for Wide {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Wide::Small(n) => write!(f, "{}", n),
+            Wide::Big(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow_goes_big() {
+        let max = Wide::from(i128::MAX);
+
+        let bigger = max.clone() + Wide::from(1);
+        assert!(matches!(bigger, Wide::Big(_)));
+        assert_eq!(
+            bigger.to_string(),
+            "170141183460469231731687303715884105728"
+        );
+
+        // and comes back down once it fits again
+        let back = bigger - Wide::from(1);
+        assert_eq!(back, max);
+        assert!(matches!(back, Wide::Small(_)));
+
+        let cubed = Wide::from(u64::MAX) * Wide::from(u64::MAX) * Wide::from(u64::MAX);
+        assert_eq!(
+            cubed.to_string(),
+            "6277101735386680762814942322444851025767571854389858533375"
+        );
+
+        assert!(matches!(Wide::from(u128::MAX), Wide::Big(_)));
+    }
+
+    #[test]
+    fn powers_and_sums() {
+        assert_eq!(Wide::pow2(10), Wide::from(1024));
+        assert_eq!(Wide::pow2(127), Wide::from(1_u128 << 127));
+        assert_eq!(
+            Wide::pow2(130).to_string(),
+            "1361129467683753853853498429727072845824"
+        );
+
+        let total: Wide = (0..200).map(Wide::pow2).sum();
+        assert_eq!(total + Wide::from(1), Wide::pow2(200));
+    }
+
+    #[test]
+    fn ordering() {
+        let mut nums = vec![
+            Wide::pow2(140),
+            Wide::from(-5),
+            Wide::from(i128::MAX),
+            Wide::from(3),
+        ];
+        nums.sort();
+        assert_eq!(
+            nums,
+            vec![
+                Wide::from(-5),
+                Wide::from(3),
+                Wide::from(i128::MAX),
+                Wide::pow2(140)
+            ]
+        );
+    }
+}
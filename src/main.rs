@@ -11,6 +11,11 @@ use std::fmt::{Display, Formatter, Write};
 use std::time::Instant;
 
 mod helpers;
+mod params;
+mod render;
+mod server;
+mod solutions;
+mod svg;
 
 mod day01;
 mod day02;
@@ -42,6 +47,8 @@ mod day25;
 enum Side {
     A,
     B,
+    /// Both parts, sharing a single parse where the day supports it
+    Both,
 }
 
 impl Display for Side {
@@ -49,16 +56,323 @@ impl Display for Side {
         match self {
             Side::A => f.write_char('a'),
             Side::B => f.write_char('b'),
+            Side::Both => f.write_str("both"),
         }
     }
 }
 
+fn read_input(day: i32) -> Result<String, String> {
+    let path = format!("input/{:02}.txt", day);
+    std::fs::read_to_string(&path).map_err(|e| format!("Cannot read '{}': {}", path, e))
+}
+
+fn ms(d: std::time::Duration) -> f32 {
+    d.as_secs_f32() * 1000.0
+}
+
+fn print_params(label: &str, params: &params::Params) {
+    if !params.is_empty() {
+        println!("{}{}", label, params);
+    }
+}
+
+fn run_both(day: i32, overrides: &[(String, String)]) -> Result<(), String> {
+    let input = read_input(day)?;
+
+    let day =
+        u32::try_from(day).map_err(|_| format!("Day {}, side both is not yet supported", day))?;
+    let report = solutions::run_both(day, &input, overrides)?;
+
+    println!("Day {} -- both:", day);
+    match report.parse {
+        Some(parse) => println!("Parsed once in {0:3} ms", ms(parse)),
+        None => match solutions::unshared_reason(day) {
+            Some(reason) => println!(
+                "(no shared parse, since {}; each part parses for itself)",
+                reason
+            ),
+            None => println!("(no shared parse; each part parses for itself)"),
+        },
+    }
+    println!("a: {}", report.a.answer);
+    println!("Took {0:3} ms", ms(report.a.elapsed));
+    print_params("Params: ", &report.a.params);
+    println!("b: {}", report.b.answer);
+    println!("Took {0:3} ms", ms(report.b.elapsed));
+    print_params("Params: ", &report.b.params);
+
+    Ok(())
+}
+
+/// Day 2 questions about bags of cubes, rather than the puzzle's fixed one
+fn bag_query(args: &[String]) -> Result<(), String> {
+    let input = read_input(2)?;
+
+    match args {
+        [query, bag] if query == "possible" => {
+            let bag = day02::parse_bag(bag)?;
+            let games = day02::possible_games(&input, &bag)?;
+
+            println!("Games possible with {}:", bag);
+            println!("{}", games.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", "));
+            println!("Sum of IDs: {}", games.iter().sum::<usize>());
+            Ok(())
+        }
+        [query, rest @ ..] if query == "minimum" && rest.len() <= 1 => {
+            let ids: Option<Vec<usize>> = rest
+                .first()
+                .map(|ids| {
+                    ids.split(',')
+                        .map(|id| {
+                            id.trim()
+                                .parse()
+                                .map_err(|_| format!("Cannot parse game ID '{}'", id))
+                        })
+                        .collect()
+                })
+                .transpose()?;
+
+            let bag = day02::minimum_bag(&input, ids.as_deref())?;
+
+            println!("Minimum bag: {}", bag);
+            println!("Power: {}", bag.power(day02::COLOURS));
+            Ok(())
+        }
+        [query, rest @ ..] if query == "report" && rest.len() <= 2 => {
+            let format = rest.first().map(|f| f.as_str()).unwrap_or("table");
+            let bag = day02::parse_bag(rest.get(1).map(|b| b.as_str()).unwrap_or(day02::PARAMS_A[0].default))?;
+            let report = day02::report(&input, &bag)?;
+
+            match format {
+                "table" => println!("{}", report.to_table()),
+                "json" => println!("{}", report.to_json()),
+                other => return Err(format!("Report format should be 'table' or 'json', not '{}'", other)),
+            }
+            Ok(())
+        }
+        _ => Err("Usage: [run] bag possible \"[count colour, ...]\" | [run] bag minimum [id,id,...] | [run] bag report [table|json] [bag] ; eg:\n\tcargo run --release -- bag possible \"12 red, 13 green, 14 blue\"".to_string()),
+    }
+}
+
+fn render_day(day: i32) -> Result<render::Canvas, String> {
+    match day {
+        3 => Ok(day03::render()),
+        10 => Ok(day10::render()),
+        11 => Ok(day11::render()),
+        13 => Ok(day13::render()),
+        day => Err(format!("Day {} does not support rendering", day)),
+    }
+}
+
+fn svg_day(day: i32) -> Result<svg::Svg, String> {
+    match day {
+        10 => Ok(day10::svg()),
+        11 => Ok(day11::svg()),
+        13 => Ok(day13::svg()),
+        day => Err(format!("Day {} does not support SVG export", day)),
+    }
+}
+
+fn explain_day(day: i32, side: Side, overrides: &[(String, String)]) -> Result<String, String> {
+    let unsupported = || format!("Day {} does not support explain mode", day);
+
+    match side {
+        // whichever parts the day can explain
+        Side::Both => {
+            let explained: Vec<String> = [Side::A, Side::B]
+                .into_iter()
+                .filter_map(|side| {
+                    explain_side(day, side, overrides)
+                        .map(|text| text.map(|text| format!("{}:\n{}", side, text)))
+                })
+                .collect::<Result<_, _>>()?;
+
+            if explained.is_empty() {
+                return Err(unsupported());
+            }
+            Ok(explained.join("\n"))
+        }
+        side => explain_side(day, side, overrides).unwrap_or_else(|| Err(unsupported())),
+    }
+}
+
+/// None if the day doesn't explain that part
+fn explain_side(
+    day: i32,
+    side: Side,
+    overrides: &[(String, String)],
+) -> Option<Result<String, String>> {
+    let explained = match (day, side) {
+        (1, Side::A) => read_input(day).map(|input| day01::explain_a(&input).to_string()),
+        (1, Side::B) => read_input(day).and_then(|input| {
+            let params = params::Params::with_overrides(day01::PARAMS_B, overrides);
+            Ok(day01::explain_b(&input, &params)?.to_string())
+        }),
+        (4, Side::B) => read_input(day).and_then(|input| {
+            let params = params::Params::with_overrides(day04::PARAMS_B, overrides);
+            Ok(day04::trace_b(&input, &params)?.to_string())
+        }),
+        _ => return None,
+    };
+
+    Some(explained)
+}
+
+fn dot_day(day: i32) -> Result<String, String> {
+    match day {
+        3 => Ok(day03::dot()),
+        day => Err(format!("Day {} does not support DOT export", day)),
+    }
+}
+
+fn run_side(day: i32, side: Side, overrides: &[(String, String)]) -> Result<(), String> {
+    // registered solutions go through the registry, so they can take parameters
+    if let Some(solution) = u32::try_from(day)
+        .ok()
+        .and_then(|day| solutions::find(day, side))
+    {
+        let input = read_input(day)?;
+        let report = solutions::run(solution, &input, overrides)?;
+
+        println!("Day {} -- {}:\n{}", day, side, report.answer);
+        println!("Took {0:3} ms", ms(report.elapsed));
+        print_params("Params: ", &report.params);
+
+        return Ok(());
+    }
+
+    if let Some((key, _)) = overrides.first() {
+        return Err(format!(
+            "Unknown parameter '{}'; day {}, side {} has no parameters",
+            key, day, side
+        ));
+    }
+
+    let start = Instant::now();
+
+    let out: String = match (day, side) {
+        (1, Side::A) => Ok(day01::a().to_string()),
+        (1, Side::B) => Ok(day01::b().to_string()),
+        (2, Side::A) => Ok(day02::a().to_string()),
+        (2, Side::B) => Ok(day02::b().to_string()),
+        (3, Side::A) => Ok(day03::a().to_string()),
+        (3, Side::B) => Ok(day03::b().to_string()),
+        (4, Side::A) => Ok(day04::a().to_string()),
+        (4, Side::B) => Ok(day04::b().to_string()),
+        (5, Side::A) => Ok(day05::a().to_string()),
+        (5, Side::B) => Ok(day05::b().to_string()),
+        (6, Side::A) => Ok(day06::a().to_string()),
+        (6, Side::B) => Ok(day06::b().to_string()),
+        (7, Side::A) => Ok(day07::a()),
+        (7, Side::B) => Ok(day07::b()),
+        (8, Side::A) => Ok(day08::a()),
+        (8, Side::B) => Ok(day08::b()),
+        (9, Side::A) => Ok(day09::a()),
+        (9, Side::B) => Ok(day09::b()),
+        (10, Side::A) => Ok(day10::a()),
+        (10, Side::B) => Ok(day10::b()),
+        (11, Side::A) => Ok(day11::a()),
+        (11, Side::B) => Ok(day11::b()),
+        (12, Side::A) => Ok(day12::a()),
+        (12, Side::B) => Ok(day12::b()),
+        (13, Side::A) => Ok(day13::a()),
+        (13, Side::B) => Ok(day13::b()),
+        (14, Side::A) => Ok(day14::a()),
+        (14, Side::B) => Ok(day14::b()),
+        (15, Side::A) => Ok(day15::a()),
+        (15, Side::B) => Ok(day15::b()),
+        (16, Side::A) => Ok(day16::a()),
+        (16, Side::B) => Ok(day16::b()),
+        (17, Side::A) => Ok(day17::a()),
+        (17, Side::B) => Ok(day17::b()),
+        (18, Side::A) => Ok(day18::a()),
+        (18, Side::B) => Ok(day18::b()),
+        (19, Side::A) => Ok(day19::a()),
+        (19, Side::B) => Ok(day19::b()),
+        (20, Side::A) => Ok(day20::a()),
+        (20, Side::B) => Ok(day20::b()),
+        (21, Side::A) => Ok(day21::a()),
+        (21, Side::B) => Ok(day21::b()),
+        (22, Side::A) => Ok(day22::a()),
+        (22, Side::B) => Ok(day22::b()),
+        (23, Side::A) => Ok(day23::a()),
+        (23, Side::B) => Ok(day23::b()),
+        (24, Side::A) => Ok(day24::a()),
+        (24, Side::B) => Ok(day24::b()),
+        (25, Side::A) => Ok(day25::a()),
+        (25, Side::B) => Ok(day25::b()),
+        (day, side) => Err(format!("Day {}, side {} is not yet supported", day, side)),
+    }?;
+
+    let elapsed = start.elapsed();
+
+    println!("Day {} -- {}:\n{}", day, side, out);
+    println!("Took {0:3} ms", ms(elapsed));
+
+    Ok(())
+}
+
 fn main() -> Result<(), String> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+
+    if args.get(1).map(|s| s.as_str()) == Some("serve") {
+        return match args.as_slice() {
+            [_, _, flag, port] if flag == "--port" => {
+                let port: u16 = port
+                    .parse()
+                    .map_err(|_| format!("Cannot parse port '{}'", port))?;
+                server::serve(port)
+            }
+            _ => Err("Usage: [run] serve --port [port] ; eg:\n\tcargo run --release -- serve --port 8080".to_string()),
+        };
+    }
+
+    if args.get(1).map(|s| s.as_str()) == Some("bag") {
+        return bag_query(&args[2..]);
+    }
+
+    let should_render = args.iter().any(|arg| arg == "--render");
+    args.retain(|arg| arg != "--render");
+
+    let should_explain = args.iter().any(|arg| arg == "--explain");
+    args.retain(|arg| arg != "--explain");
+
+    let svg_path: Option<String> = match args.iter().position(|arg| arg == "--svg") {
+        Some(ind) => {
+            if ind + 1 >= args.len() {
+                return Err("--svg requires a path".to_string());
+            }
+            let path = args.remove(ind + 1);
+            args.remove(ind);
+            Some(path)
+        }
+        None => None,
+    };
+
+    let dot_path: Option<String> = match args.iter().position(|arg| arg == "--dot") {
+        Some(ind) => {
+            if ind + 1 >= args.len() {
+                return Err("--dot requires a path".to_string());
+            }
+            let path = args.remove(ind + 1);
+            args.remove(ind);
+            Some(path)
+        }
+        None => None,
+    };
+
+    let mut overrides: Vec<(String, String)> = Vec::new();
+    while let Some(ind) = args.iter().position(|arg| arg == "--param") {
+        if ind + 1 >= args.len() {
+            return Err("--param requires a key=value".to_string());
+        }
+        overrides.push(params::parse_override(&args.remove(ind + 1))?);
+        args.remove(ind);
+    }
 
     if args.len() != 3 {
         Err(
-            "Usage: [run] [problemnumber] [subcase] ; eg:\n\tcargo run --release -- 1 a"
+            "Usage: [run] [problemnumber] [a|b|both] [--param key=value]... [--explain] [--render] [--svg path] [--dot path] ; eg:\n\tcargo run --release -- 1 a"
                 .to_string(),
         )
     } else {
@@ -68,72 +382,44 @@ fn main() -> Result<(), String> {
         let b: Side = match args[2].as_str() {
             "a" => Ok(Side::A),
             "b" => Ok(Side::B),
+            "both" => Ok(Side::Both),
             _err => Err(format!(
-                "Cannot parse argument '{}' as subcase; should be 'a' or 'b'",
-                args[0].as_str()
+                "Cannot parse argument '{}' as subcase; should be 'a', 'b' or 'both'",
+                args[2].as_str()
             )),
         }?;
 
-        let start = Instant::now();
-
-        let out: String = match (a, b) {
-            (1, Side::A) => Ok(day01::a().to_string()),
-            (1, Side::B) => Ok(day01::b().to_string()),
-            (2, Side::A) => Ok(day02::a().to_string()),
-            (2, Side::B) => Ok(day02::b().to_string()),
-            (3, Side::A) => Ok(day03::a().to_string()),
-            (3, Side::B) => Ok(day03::b().to_string()),
-            (4, Side::A) => Ok(day04::a().to_string()),
-            (4, Side::B) => Ok(day04::b().to_string()),
-            (5, Side::A) => Ok(day05::a().to_string()),
-            (5, Side::B) => Ok(day05::b().to_string()),
-            (6, Side::A) => Ok(day06::a().to_string()),
-            (6, Side::B) => Ok(day06::b().to_string()),
-            (7, Side::A) => Ok(day07::a()),
-            (7, Side::B) => Ok(day07::b()),
-            (8, Side::A) => Ok(day08::a()),
-            (8, Side::B) => Ok(day08::b()),
-            (9, Side::A) => Ok(day09::a()),
-            (9, Side::B) => Ok(day09::b()),
-            (10, Side::A) => Ok(day10::a()),
-            (10, Side::B) => Ok(day10::b()),
-            (11, Side::A) => Ok(day11::a()),
-            (11, Side::B) => Ok(day11::b()),
-            (12, Side::A) => Ok(day12::a()),
-            (12, Side::B) => Ok(day12::b()),
-            (13, Side::A) => Ok(day13::a()),
-            (13, Side::B) => Ok(day13::b()),
-            (14, Side::A) => Ok(day14::a()),
-            (14, Side::B) => Ok(day14::b()),
-            (15, Side::A) => Ok(day15::a()),
-            (15, Side::B) => Ok(day15::b()),
-            (16, Side::A) => Ok(day16::a()),
-            (16, Side::B) => Ok(day16::b()),
-            (17, Side::A) => Ok(day17::a()),
-            (17, Side::B) => Ok(day17::b()),
-            (18, Side::A) => Ok(day18::a()),
-            (18, Side::B) => Ok(day18::b()),
-            (19, Side::A) => Ok(day19::a()),
-            (19, Side::B) => Ok(day19::b()),
-            (20, Side::A) => Ok(day20::a()),
-            (20, Side::B) => Ok(day20::b()),
-            (21, Side::A) => Ok(day21::a()),
-            (21, Side::B) => Ok(day21::b()),
-            (22, Side::A) => Ok(day22::a()),
-            (22, Side::B) => Ok(day22::b()),
-            (23, Side::A) => Ok(day23::a()),
-            (23, Side::B) => Ok(day23::b()),
-            (24, Side::A) => Ok(day24::a()),
-            (24, Side::B) => Ok(day24::b()),
-            (25, Side::A) => Ok(day25::a()),
-            (25, Side::B) => Ok(day25::b()),
-            (day, side) => Err(format!("Day {}, side {} is not yet supported", day, side)),
-        }?;
+        if b == Side::Both {
+            run_both(a, &overrides)?;
+        } else {
+            run_side(a, b, &overrides)?;
+        }
 
-        let elapsed = start.elapsed();
+        if should_explain {
+            let explanation = explain_day(a, b, &overrides)?;
+            println!();
+            println!("{}", explanation);
+        }
 
-        println!("Day {} -- {}:\n{}", a, b, out);
-        println!("Took {0:3} ms", elapsed.as_secs_f32() * 1000.0);
+        if should_render {
+            let canvas = render_day(a)?;
+            println!();
+            print!("{}", canvas.render(render::use_ansi()));
+        }
+
+        if let Some(path) = svg_path {
+            let svg = svg_day(a)?;
+            std::fs::write(&path, svg.to_string())
+                .map_err(|e| format!("Could not write SVG to '{}': {}", path, e))?;
+            println!("Wrote SVG to {}", path);
+        }
+
+        if let Some(path) = dot_path {
+            let dot = dot_day(a)?;
+            std::fs::write(&path, dot)
+                .map_err(|e| format!("Could not write DOT to '{}': {}", path, e))?;
+            println!("Wrote DOT to {}", path);
+        }
 
         Ok(())
     }
@@ -0,0 +1,163 @@
+//! Tunable constants for the solutions (bag limits, expansion factors and so on). Each solution
+//! declares its parameters with the puzzle's own values as defaults, and the CLI can override them
+//! with `--param key=value`.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Param {
+    pub key: &'static str,
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+/// Values for a solution's declared parameters: the defaults, with any overrides applied
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Params {
+    // in declaration order
+    values: Vec<(&'static str, String)>,
+}
+
+impl Params {
+    pub fn defaults(declared: &[Param]) -> Self {
+        Params {
+            values: declared
+                .iter()
+                .map(|p| (p.key, p.default.to_string()))
+                .collect(),
+        }
+    }
+
+    /// The defaults, with any overrides for keys that were declared; overrides for anything else
+    /// are ignored (see `check_known` for catching typos)
+    pub fn with_overrides(declared: &[Param], overrides: &[(String, String)]) -> Self {
+        let mut params = Self::defaults(declared);
+
+        for (key, value) in overrides {
+            if let Some(slot) = params.values.iter_mut().find(|(k, _)| k == key) {
+                slot.1 = value.clone();
+            }
+        }
+
+        params
+    }
+
+    /// The value for the key, parsed as whatever type the solution wants
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        let (_, value) = self
+            .values
+            .iter()
+            .find(|(k, _)| *k == key)
+            .ok_or_else(|| format!("Parameter '{}' was never declared", key))?;
+
+        value.parse().map_err(|_| {
+            format!(
+                "Cannot parse '{}' as a value for parameter '{}'",
+                value, key
+            )
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (*k, v.as_str()))
+    }
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a "key=value" argument
+pub fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Parameter '{}' should look like key=value", arg)),
+    }
+}
+
+/// Errors if any of the overrides isn't declared in any of the lists, listing what is
+pub fn check_known(declared: &[&[Param]], overrides: &[(String, String)]) -> Result<(), String> {
+    for (key, _) in overrides {
+        if !declared
+            .iter()
+            .any(|list| list.iter().any(|p| p.key == key))
+        {
+            let known: Vec<String> = declared
+                .iter()
+                .flat_map(|list| list.iter())
+                .map(|p| format!("{} ({}; default {})", p.key, p.description, p.default))
+                .collect();
+
+            return Err(if known.is_empty() {
+                format!("Unknown parameter '{}'; this solution has none", key)
+            } else {
+                format!("Unknown parameter '{}'; known: {}", key, known.join(", "))
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECLARED: &[Param] = &[
+        Param {
+            key: "width",
+            default: "10",
+            description: "how wide",
+        },
+        Param {
+            key: "name",
+            default: "AAA",
+            description: "what to call it",
+        },
+    ];
+
+    #[test]
+    fn defaults_and_overrides() {
+        let params = Params::defaults(DECLARED);
+        assert_eq!(params.get::<usize>("width"), Ok(10));
+        assert_eq!(params.to_string(), "width=10, name=AAA");
+
+        let overrides = vec![parse_override("width=25").unwrap()];
+        let params = Params::with_overrides(DECLARED, &overrides);
+        assert_eq!(params.get::<usize>("width"), Ok(25));
+        assert_eq!(params.get::<String>("name"), Ok("AAA".to_string()));
+
+        assert!(params.get::<usize>("name").is_err());
+        assert!(params.get::<usize>("height").is_err());
+    }
+
+    #[test]
+    fn bad_overrides() {
+        assert!(parse_override("nonsense").is_err());
+        assert!(parse_override("=5").is_err());
+        assert_eq!(
+            parse_override("key=a=b"),
+            Ok(("key".to_string(), "a=b".to_string()))
+        );
+
+        let typo = vec![("widht".to_string(), "5".to_string())];
+        let err = check_known(&[DECLARED], &typo).unwrap_err();
+        assert!(err.starts_with("Unknown parameter 'widht'; known: width (how wide; default 10)"));
+
+        assert!(check_known(&[&[], DECLARED], &[("name".to_string(), "x".to_string())]).is_ok());
+        assert!(check_known(&[&[]], &typo).is_err());
+    }
+}
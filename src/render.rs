@@ -0,0 +1,226 @@
+//! Terminal rendering for the grid days. A day builds a `Canvas` out of its input, paints whatever
+//! overlays it wants on top, and `Canvas::render` turns that into either ANSI-coloured text or
+//! plain ASCII (where each overlay falls back to a replacement character instead of a colour).
+
+use std::io::IsTerminal;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+            Colour::Grey => 90,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Cell {
+    ch: char,
+    colour: Option<Colour>,
+    // what to draw instead of `ch` when colours aren't available; None means just use `ch`
+    plain: Option<char>,
+}
+
+impl Cell {
+    fn new(ch: char) -> Self {
+        Cell {
+            ch,
+            colour: None,
+            plain: None,
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct LegendEntry {
+    colour: Colour,
+    plain: Option<char>,
+    description: String,
+}
+
+/// A (possibly ragged) block of characters with colour overlays. Coordinates are (x, y), with
+/// (0, 0) in the top left, same as the input text.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Canvas {
+    rows: Vec<Vec<Cell>>,
+    legend: Vec<LegendEntry>,
+}
+
+impl Canvas {
+    pub fn from_text(text: &str) -> Self {
+        let rows = text
+            .lines()
+            .map(|line| line.chars().map(Cell::new).collect())
+            .collect();
+
+        Canvas {
+            rows,
+            legend: Vec::new(),
+        }
+    }
+
+    /// Stacks the canvases on top of each other, with a blank line between each; legends are
+    /// merged (duplicates are dropped).
+    pub fn stack(canvases: Vec<Canvas>) -> Self {
+        let mut out = Canvas::default();
+
+        for (i, canvas) in canvases.into_iter().enumerate() {
+            if i > 0 {
+                out.rows.push(Vec::new());
+            }
+
+            out.rows.extend(canvas.rows);
+
+            for entry in canvas.legend {
+                if !out.legend.contains(&entry) {
+                    out.legend.push(entry);
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Colours the cell, but leaves its character alone (in both modes)
+    pub fn paint(&mut self, x: usize, y: usize, colour: Colour) {
+        self.rows[y][x].colour = Some(colour);
+    }
+
+    /// Colours the cell, and swaps in `plain` for its character when rendering without colour
+    pub fn mark(&mut self, x: usize, y: usize, colour: Colour, plain: char) {
+        let cell = &mut self.rows[y][x];
+        cell.colour = Some(colour);
+        cell.plain = Some(plain);
+    }
+
+    /// Overwrites the character in the cell, padding the row with spaces if necessary
+    pub fn set_char(&mut self, x: usize, y: usize, ch: char) {
+        let row = &mut self.rows[y];
+        while row.len() <= x {
+            row.push(Cell::new(' '));
+        }
+        row[x] = Cell::new(ch);
+    }
+
+    /// Adds a new row of spaces at the top of the canvas
+    pub fn push_top_row(&mut self) {
+        self.rows.insert(0, Vec::new());
+    }
+
+    /// Adds a new column of spaces at the left of the canvas (empty rows are left empty)
+    pub fn push_left_column(&mut self) {
+        for row in self.rows.iter_mut().filter(|row| !row.is_empty()) {
+            row.insert(0, Cell::new(' '));
+        }
+    }
+
+    pub fn add_legend(&mut self, colour: Colour, plain: Option<char>, description: &str) {
+        self.legend.push(LegendEntry {
+            colour,
+            plain,
+            description: description.to_string(),
+        });
+    }
+
+    pub fn render(&self, ansi: bool) -> String {
+        let mut out = String::new();
+
+        for row in self.rows.iter() {
+            for cell in row.iter() {
+                write_cell(&mut out, *cell, ansi);
+            }
+            out.push('\n');
+        }
+
+        if !self.legend.is_empty() {
+            out.push('\n');
+        }
+
+        for entry in self.legend.iter() {
+            let sample = Cell {
+                ch: '#',
+                colour: Some(entry.colour),
+                plain: entry.plain,
+            };
+
+            if ansi || entry.plain.is_some() {
+                write_cell(&mut out, sample, ansi);
+                out.push_str(" = ");
+            }
+            out.push_str(&entry.description);
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+fn write_cell(out: &mut String, cell: Cell, ansi: bool) {
+    match (ansi, cell.colour) {
+        (true, Some(colour)) => {
+            out.push_str(&format!("\x1b[{}m{}\x1b[0m", colour.ansi_code(), cell.ch));
+        }
+        (true, None) => out.push(cell.ch),
+        (false, _) => out.push(cell.plain.unwrap_or(cell.ch)),
+    }
+}
+
+/// Whether rendered output should use colour; only when stdout is an actual terminal
+pub fn use_ansi() -> bool {
+    std::io::stdout().is_terminal()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_uses_fallback_chars() {
+        let mut canvas = Canvas::from_text("ab\ncd");
+        canvas.mark(1, 0, Colour::Red, 'X');
+        canvas.paint(0, 1, Colour::Blue);
+
+        assert_eq!(canvas.render(false), "aX\ncd\n");
+    }
+
+    #[test]
+    fn ansi_uses_colours() {
+        let mut canvas = Canvas::from_text("ab");
+        canvas.mark(1, 0, Colour::Red, 'X');
+
+        assert_eq!(canvas.render(true), "a\x1b[31mb\x1b[0m\n");
+    }
+
+    #[test]
+    fn stack_and_legend() {
+        let mut a = Canvas::from_text("a");
+        a.add_legend(Colour::Green, Some('G'), "green things");
+        let mut b = Canvas::from_text("b");
+        b.add_legend(Colour::Green, Some('G'), "green things");
+        b.add_legend(Colour::Grey, None, "grey things");
+
+        let stacked = Canvas::stack(vec![a, b]);
+
+        assert_eq!(stacked.rows.len(), 3);
+        assert_eq!(
+            stacked.render(false),
+            "a\n\nb\n\nG = green things\ngrey things\n"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! A tiny (std-only, one request per connection) HTTP server exposing the registered solutions as
+//! a JSON API. Endpoints:
+//!
+//!     GET  /days                  -- lists the registered solutions
+//!     POST /solve/{day}/{part}    -- runs the solution on the request body
+//!
+//! Solvers signal bad input by panicking, so each solve runs under `catch_unwind` and a panic
+//! comes back as an error in the response instead of taking the server down. Solutions always run
+//! with their default parameters, which are listed by `/days` and echoed in each answer.
+
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use crate::helpers::json::{json_array, json_object, json_string};
+use crate::params::{Param, Params};
+use crate::solutions::{self, SOLUTIONS};
+use crate::Side;
+
+/// Bigger than any real puzzle input by a long way; anything past this is refused unread
+const MAX_BODY_BYTES: usize = 1 << 22;
+
+/// Connections are handled one at a time, so a client which goes quiet can't be waited on forever
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client trickling in a byte at a time never trips `READ_TIMEOUT`, so the whole request has to
+/// arrive within this long too
+const REQUEST_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Longest request line or header line, line ending included
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+const MAX_HEADERS: usize = 100;
+
+pub fn serve(port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Could not bind to port {}: {}", port, e))?;
+
+    println!("Listening on http://127.0.0.1:{}", port);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(stream) {
+                    eprintln!("Error handling connection: {}", e);
+                }
+            }
+            Err(e) => eprintln!("Error accepting connection: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: json_object([("error", json_string(message))]),
+        }
+    }
+}
+
+/// Reads from the stream, but gives up once the deadline has passed, however the reads are spread
+/// out before then
+struct DeadlineReader {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::new(
+                ErrorKind::TimedOut,
+                "Request deadline passed",
+            ));
+        }
+
+        self.stream
+            .set_read_timeout(Some(remaining.min(READ_TIMEOUT)))?;
+        self.stream.read(buf)
+    }
+}
+
+fn handle_connection(stream: TcpStream) -> std::io::Result<()> {
+    let mut reader = BufReader::new(DeadlineReader {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + REQUEST_DEADLINE,
+    });
+
+    let response = match read_request(&mut reader) {
+        Ok(request) => handle(&request),
+        Err(response) => response,
+    };
+
+    write_response(stream, &response)
+}
+
+/// The response for a failed read of the given part of the request
+fn read_error(what: &str, e: std::io::Error) -> Response {
+    match e.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => {
+            Response::error(408, &format!("Timed out reading {}", what))
+        }
+        _ => Response::error(400, &format!("Could not read {}: {}", what, e)),
+    }
+}
+
+/// Reads one line, refusing (with the given status) to read more than `MAX_LINE_BYTES` of it
+fn read_capped_line(
+    reader: &mut impl BufRead,
+    what: &str,
+    too_long: u16,
+) -> Result<String, Response> {
+    let mut line = String::new();
+    reader
+        .take(MAX_LINE_BYTES as u64 + 1)
+        .read_line(&mut line)
+        .map_err(|e| read_error(what, e))?;
+
+    if line.len() > MAX_LINE_BYTES {
+        return Err(Response::error(
+            too_long,
+            &format!("The {} is over the limit of {} bytes", what, MAX_LINE_BYTES),
+        ));
+    }
+
+    Ok(line)
+}
+
+/// Reads one request; the error is the response to send back instead
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let bad_request = |message: String| Response::error(400, &message);
+
+    let request_line = read_capped_line(reader, "request line", 414)?;
+
+    let mut parts = request_line.split_ascii_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => {
+            return Err(bad_request(format!(
+                "Malformed request line '{}'",
+                request_line.trim()
+            )))
+        }
+    };
+
+    let mut content_length = 0;
+    let mut num_headers = 0;
+
+    loop {
+        let header = read_capped_line(reader, "header", 431)?;
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        num_headers += 1;
+        if num_headers > MAX_HEADERS {
+            return Err(Response::error(
+                431,
+                &format!("More than {} headers", MAX_HEADERS),
+            ));
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request(format!("Bad Content-Length '{}'", value.trim())))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(
+            413,
+            &format!(
+                "Body of {} bytes is over the limit of {} bytes",
+                content_length, MAX_BODY_BYTES
+            ),
+        ));
+    }
+
+    let mut body = vec![0; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| read_error("body", e))?;
+    let body = String::from_utf8(body).map_err(|_| bad_request("Body should be UTF-8".into()))?;
+
+    Ok(Request { method, path, body })
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        _ => "Unknown",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+
+    stream.flush()
+}
+
+fn handle(request: &Request) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => list_days(),
+        ("POST", ["solve", day, part]) => solve(day, part, &request.body),
+        (_, ["days"]) | (_, ["solve", _, _]) => Response::error(405, "Method not allowed"),
+        _ => Response::error(404, &format!("No route for '{}'", request.path)),
+    }
+}
+
+fn list_days() -> Response {
+    let entries = SOLUTIONS.iter().map(|s| {
+        json_object([
+            ("day", s.day.to_string()),
+            ("part", json_string(&s.side.to_string())),
+            ("params", json_param_list(s.params)),
+        ])
+    });
+
+    Response {
+        status: 200,
+        body: json_object([("days", json_array(entries))]),
+    }
+}
+
+fn solve(day: &str, part: &str, input: &str) -> Response {
+    let Ok(day) = day.parse::<u32>() else {
+        return Response::error(400, &format!("Cannot parse day '{}'", day));
+    };
+
+    let side = match part {
+        "a" => Side::A,
+        "b" => Side::B,
+        other => {
+            return Response::error(400, &format!("Part should be 'a' or 'b', not '{}'", other));
+        }
+    };
+
+    let Some(solution) = solutions::find(day, side) else {
+        return Response::error(404, &format!("Day {}, side {} is not supported", day, side));
+    };
+
+    let start = Instant::now();
+    let result = catch_unwind(AssertUnwindSafe(|| solutions::run(solution, input, &[])));
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let (status, answer, error) = match result {
+        Ok(Ok(report)) => (200, json_string(&report.answer), "null".to_string()),
+        Ok(Err(message)) => (422, "null".to_string(), json_string(&message)),
+        Err(payload) => (
+            422,
+            "null".to_string(),
+            json_string(&panic_message(payload)),
+        ),
+    };
+
+    Response {
+        status,
+        body: json_object([
+            ("day", day.to_string()),
+            ("part", json_string(&side.to_string())),
+            ("answer", answer),
+            ("elapsed_ms", elapsed_ms.to_string()),
+            ("params", json_params(&Params::defaults(solution.params))),
+            ("error", error),
+        ]),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Solver panicked".to_string()
+    }
+}
+
+fn json_param_list(declared: &[Param]) -> String {
+    json_array(declared.iter().map(|p| {
+        json_object([
+            ("key", json_string(p.key)),
+            ("default", json_string(p.default)),
+            ("description", json_string(p.description)),
+        ])
+    }))
+}
+
+fn json_params(params: &Params) -> String {
+    json_object(params.iter().map(|(key, value)| (key, json_string(value))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    fn request(method: &str, path: &str, body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn solves_posted_input() {
+        let response = handle(&request("POST", "/solve/1/a", "1abc2\npqr3stu8vwx"));
+
+        assert_eq!(response.status, 200);
+        assert!(response
+            .body
+            .starts_with(r#"{"day":1,"part":"a","answer":"50","elapsed_ms":"#));
+        assert!(response.body.ends_with(r#""params":{},"error":null}"#));
+    }
+
+    #[test]
+    fn reports_default_params() {
+        let response = handle(&request(
+            "POST",
+            "/solve/2/a",
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        ));
+
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains(r#""answer":"1""#));
+        assert!(response
+            .body
+            .ends_with(r#""params":{"bag":"12 red, 13 green, 14 blue"},"error":null}"#));
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let response = handle(&request("POST", "/solve/2/b", "no games here"));
+
+        assert_eq!(response.status, 422);
+        assert!(response.body.contains(r#""answer":null"#));
+        assert!(response
+            .body
+            .contains(r#""error":"Cannot parse game 'no games here'"#));
+    }
+
+    #[test]
+    fn routing_errors() {
+        assert_eq!(handle(&request("POST", "/solve/25/a", "")).status, 404);
+        assert_eq!(handle(&request("POST", "/solve/1/c", "")).status, 400);
+        assert_eq!(handle(&request("GET", "/solve/1/a", "")).status, 405);
+        assert_eq!(handle(&request("GET", "/nope", "")).status, 404);
+    }
+
+    #[test]
+    fn lists_days() {
+        let response = handle(&request("GET", "/days", ""));
+
+        assert_eq!(response.status, 200);
+        assert!(response.body.starts_with(
+            r#"{"days":[{"day":1,"part":"a","params":[]},{"day":1,"part":"b","params":[{"key":"vocabulary","#
+        ));
+        assert!(response.body.contains(
+            r#"{"day":11,"part":"b","params":[{"key":"expansion","default":"1000000","description":"#
+        ));
+    }
+
+    #[test]
+    fn refuses_huge_bodies() {
+        let raw = "POST /solve/1/a HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n";
+        let response = read_request(&mut raw.as_bytes()).unwrap_err();
+
+        assert_eq!(response.status, 413);
+
+        let raw = "POST /solve/1/a HTTP/1.1\r\nContent-Length: 5\r\n\r\n1a2b3";
+        assert_eq!(read_request(&mut raw.as_bytes()).unwrap().body, "1a2b3");
+    }
+
+    #[test]
+    fn refuses_huge_headers() {
+        let long_path = format!("GET /{} HTTP/1.1\r\n\r\n", "x".repeat(MAX_LINE_BYTES));
+        assert_eq!(
+            read_request(&mut long_path.as_bytes()).unwrap_err().status,
+            414
+        );
+
+        let long_header = format!(
+            "GET /days HTTP/1.1\r\nX-Big: {}\r\n\r\n",
+            "x".repeat(MAX_LINE_BYTES)
+        );
+        assert_eq!(
+            read_request(&mut long_header.as_bytes())
+                .unwrap_err()
+                .status,
+            431
+        );
+
+        let many_headers = format!(
+            "GET /days HTTP/1.1\r\n{}\r\n",
+            "X-Small: 1\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert_eq!(
+            read_request(&mut many_headers.as_bytes())
+                .unwrap_err()
+                .status,
+            431
+        );
+
+        let enough_headers = format!(
+            "GET /days HTTP/1.1\r\n{}\r\n",
+            "X-Small: 1\r\n".repeat(MAX_HEADERS)
+        );
+        assert_eq!(
+            read_request(&mut enough_headers.as_bytes()).unwrap().path,
+            "/days"
+        );
+    }
+
+    #[test]
+    fn stops_reading_at_the_deadline() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        // a request line, and then nothing more
+        write!(client, "GET /days HTTP/1.1\r\n").unwrap();
+
+        let mut reader = BufReader::new(DeadlineReader {
+            stream,
+            deadline: Instant::now() + Duration::from_millis(50),
+        });
+        assert_eq!(read_request(&mut reader).unwrap_err().status, 408);
+    }
+
+    #[test]
+    fn round_trip_on_localhost() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream).unwrap();
+        });
+
+        let body = "Time:      7  15   30\nDistance:  9  40  200";
+        let mut client = TcpStream::connect(addr).unwrap();
+        write!(
+            client,
+            "POST /solve/6/a HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(r#""answer":"288""#));
+    }
+}
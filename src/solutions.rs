@@ -0,0 +1,452 @@
+//! Registry of the solutions that are actually implemented, keyed by day and side, so they can be
+//! run against arbitrary input (rather than the checked-in input files).
+
+use std::time::{Duration, Instant};
+
+use crate::params::{check_known, Param, Params};
+use crate::Side;
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+};
+
+pub struct Solution {
+    pub day: u32,
+    pub side: Side,
+    pub params: &'static [Param],
+    pub solve: fn(&str, &Params) -> Result<String, String>,
+}
+
+pub const SOLUTIONS: &[Solution] = &[
+    Solution {
+        day: 1,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day01::a_with_multi_input(input).to_string()),
+    },
+    Solution {
+        day: 1,
+        side: Side::B,
+        params: day01::PARAMS_B,
+        solve: |input, params| day01::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 2,
+        side: Side::A,
+        params: day02::PARAMS_A,
+        solve: |input, params| day02::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 2,
+        side: Side::B,
+        params: &[],
+        solve: |input, params| day02::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 3,
+        side: Side::A,
+        params: day03::PARAMS_A,
+        solve: |input, params| day03::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 3,
+        side: Side::B,
+        params: day03::PARAMS_B,
+        solve: |input, params| day03::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 4,
+        side: Side::A,
+        params: day04::PARAMS_A,
+        solve: |input, params| day04::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 4,
+        side: Side::B,
+        params: day04::PARAMS_B,
+        solve: |input, params| day04::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 5,
+        side: Side::A,
+        params: day05::PARAMS_A,
+        solve: |input, params| day05::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 5,
+        side: Side::B,
+        params: day05::PARAMS_B,
+        solve: |input, params| day05::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 6,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day06::a_with_input(input).to_string()),
+    },
+    Solution {
+        day: 6,
+        side: Side::B,
+        params: &[],
+        solve: |input, _| Ok(day06::b_with_input(input).to_string()),
+    },
+    Solution {
+        day: 7,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day07::a_with_input(input).to_string()),
+    },
+    Solution {
+        day: 7,
+        side: Side::B,
+        params: &[],
+        solve: |input, _| Ok(day07::b_with_input(input).to_string()),
+    },
+    Solution {
+        day: 8,
+        side: Side::A,
+        params: day08::PARAMS_A,
+        solve: |input, params| day08::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 8,
+        side: Side::B,
+        params: day08::PARAMS_B,
+        solve: |input, params| day08::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 9,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day09::a_with_input(input).to_string()),
+    },
+    Solution {
+        day: 9,
+        side: Side::B,
+        params: &[],
+        solve: |input, _| Ok(day09::b_with_input(input).to_string()),
+    },
+    Solution {
+        day: 10,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day10::a_with_input(input).to_string()),
+    },
+    Solution {
+        day: 10,
+        side: Side::B,
+        params: &[],
+        solve: |input, _| Ok(day10::b_with_input(input).to_string()),
+    },
+    Solution {
+        day: 11,
+        side: Side::A,
+        params: day11::PARAMS_A,
+        solve: |input, params| day11::a_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 11,
+        side: Side::B,
+        params: day11::PARAMS_B,
+        solve: |input, params| day11::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 12,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day12::a_with_input(input).to_string()),
+    },
+    Solution {
+        day: 12,
+        side: Side::B,
+        params: day12::PARAMS_B,
+        solve: |input, params| day12::b_with_params(input, params).map(|n| n.to_string()),
+    },
+    Solution {
+        day: 13,
+        side: Side::A,
+        params: &[],
+        solve: |input, _| Ok(day13::a_with_input(input).to_string()),
+    },
+];
+
+pub fn find(day: u32, side: Side) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| s.day == day && s.side == side)
+}
+
+/// A day whose parsing is separate from its solving, so both parts can share one parse
+pub trait SharedParse {
+    type Model;
+
+    const PARAMS_A: &'static [Param] = &[];
+    const PARAMS_B: &'static [Param] = &[];
+
+    fn parse(input: &str) -> Result<Self::Model, String>;
+    fn a(model: &Self::Model, params: &Params) -> Result<String, String>;
+    fn b(model: &Self::Model, params: &Params) -> Result<String, String>;
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+pub struct PartReport {
+    pub answer: String,
+    pub elapsed: Duration,
+    pub params: Params,
+}
+
+impl PartReport {
+    fn run(
+        params: Params,
+        solve: impl FnOnce(&Params) -> Result<String, String>,
+    ) -> Result<Self, String> {
+        let (answer, elapsed) = timed(|| solve(&params));
+        Ok(PartReport {
+            answer: answer?,
+            elapsed,
+            params,
+        })
+    }
+}
+
+/// Answers to both parts, and how long everything took
+pub struct BothReport {
+    /// None if the day doesn't share its parse, in which case each part's time includes parsing
+    pub parse: Option<Duration>,
+    pub a: PartReport,
+    pub b: PartReport,
+}
+
+pub type RunBoth = fn(&str, &[(String, String)]) -> Result<BothReport, String>;
+
+fn run_shared<S: SharedParse>(
+    input: &str,
+    overrides: &[(String, String)],
+) -> Result<BothReport, String> {
+    check_known(&[S::PARAMS_A, S::PARAMS_B], overrides)?;
+
+    let (model, parse) = timed(|| S::parse(input));
+    let model = model?;
+
+    Ok(BothReport {
+        parse: Some(parse),
+        a: PartReport::run(Params::with_overrides(S::PARAMS_A, overrides), |p| {
+            S::a(&model, p)
+        })?,
+        b: PartReport::run(Params::with_overrides(S::PARAMS_B, overrides), |p| {
+            S::b(&model, p)
+        })?,
+    })
+}
+
+/// Days which implement `SharedParse`
+pub const SHARED: &[(u32, RunBoth)] = &[
+    (2, run_shared::<day02::Solver>),
+    (3, run_shared::<day03::Solver>),
+    (4, run_shared::<day04::Solver>),
+    (5, run_shared::<day05::Solver>),
+    (8, run_shared::<day08::Solver>),
+    (9, run_shared::<day09::Solver>),
+    (10, run_shared::<day10::Solver>),
+    (11, run_shared::<day11::Solver>),
+    (12, run_shared::<day12::Solver>),
+];
+
+/// Days which don't implement `SharedParse`, and why; `run_both` runs their parts separately, and
+/// `BothReport::parse` is None
+pub const UNSHARED: &[(u32, &str)] = &[
+    (1, "the parts scan each line with different vocabularies"),
+    (
+        6,
+        "part b reads each line as one number, ignoring the spaces",
+    ),
+    (7, "part b reads J as a joker, which changes how hands rank"),
+    (13, "part b isn't written yet"),
+];
+
+/// Why the day has no shared parse, if it doesn't
+pub fn unshared_reason(day: u32) -> Option<&'static str> {
+    UNSHARED
+        .iter()
+        .find(|(d, _)| *d == day)
+        .map(|(_, reason)| *reason)
+}
+
+/// Runs one part, with the given parameter overrides (which all have to be declared)
+pub fn run(
+    solution: &Solution,
+    input: &str,
+    overrides: &[(String, String)],
+) -> Result<PartReport, String> {
+    check_known(&[solution.params], overrides)?;
+
+    PartReport::run(Params::with_overrides(solution.params, overrides), |p| {
+        (solution.solve)(input, p)
+    })
+}
+
+/// Runs both parts on the same input, sharing the parse if the day supports it; each override
+/// goes to whichever parts declare it
+pub fn run_both(
+    day: u32,
+    input: &str,
+    overrides: &[(String, String)],
+) -> Result<BothReport, String> {
+    if let Some((_, run)) = SHARED.iter().find(|(d, _)| *d == day) {
+        return run(input, overrides);
+    }
+
+    let unsupported = || format!("Day {}, side both is not yet supported", day);
+    let a = find(day, Side::A).ok_or_else(unsupported)?;
+    let b = find(day, Side::B).ok_or_else(unsupported)?;
+
+    check_known(&[a.params, b.params], overrides)?;
+
+    let solve = |solution: &Solution| {
+        PartReport::run(Params::with_overrides(solution.params, overrides), |p| {
+            (solution.solve)(input, p)
+        })
+    };
+
+    Ok(BothReport {
+        parse: None,
+        a: solve(a)?,
+        b: solve(b)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY_5: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn shared_parse_matches_separate_parts() {
+        let report = run_both(5, DAY_5, &[]).unwrap();
+        assert!(report.parse.is_some());
+        assert_eq!(
+            report.a.answer,
+            run(find(5, Side::A).unwrap(), DAY_5, &[]).unwrap().answer
+        );
+        assert_eq!(
+            report.b.answer,
+            run(find(5, Side::B).unwrap(), DAY_5, &[]).unwrap().answer
+        );
+        assert_eq!(report.a.answer, "35");
+        assert_eq!(report.b.answer, "46");
+    }
+
+    #[test]
+    fn every_shared_day_matches_separate_parts() {
+        for (day, _) in SHARED {
+            let input = std::fs::read_to_string(format!("input/{:02}.txt", day)).unwrap();
+            let report = run_both(*day, &input, &[]).unwrap();
+            for (side, part) in [(Side::A, &report.a), (Side::B, &report.b)] {
+                let alone = run(find(*day, side).unwrap(), &input, &[]).unwrap();
+                assert_eq!(part.answer, alone.answer, "Day {} {:?}", day, side);
+            }
+        }
+    }
+
+    #[test]
+    fn falls_back_without_shared_parse() {
+        let report = run_both(6, "Time:      7  15   30\nDistance:  9  40  200", &[]).unwrap();
+        assert!(report.parse.is_none());
+        assert!(unshared_reason(6).is_some());
+        assert_eq!(report.a.answer, "288");
+        assert_eq!(report.b.answer, "71503");
+
+        // day 13 only has part a so far
+        assert!(run_both(13, "#.\n.#", &[]).is_err());
+    }
+
+    #[test]
+    fn every_day_is_shared_or_says_why_not() {
+        for day in SOLUTIONS.iter().map(|s| s.day) {
+            let shared = SHARED.iter().any(|(d, _)| *d == day);
+            assert!(shared != unshared_reason(day).is_some(), "Day {}", day);
+        }
+    }
+
+    const DAY_11: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    fn set(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn params_override_defaults() {
+        let day_11_b = find(11, Side::B).unwrap();
+
+        let report = run(day_11_b, DAY_11, &[]).unwrap();
+        assert_eq!(report.params.to_string(), "expansion=1000000");
+
+        let report = run(day_11_b, DAY_11, &[set("expansion", "100")]).unwrap();
+        assert_eq!(report.answer, "8410");
+        assert_eq!(report.params.to_string(), "expansion=100");
+
+        assert!(run(day_11_b, DAY_11, &[set("expansion", "lots")]).is_err());
+        assert!(run(day_11_b, DAY_11, &[set("expnasion", "100")]).is_err());
+    }
+
+    #[test]
+    fn params_split_between_parts() {
+        // day 8 shares its parse, and each part has its own parameters
+        let input = "LR
+
+AAA = (AAB, XXX)
+AAB = (ZZZ, XXX)
+ZZZ = (AAB, XXX)
+XXX = (XXX, XXX)";
+
+        let report = run_both(8, input, &[set("start", "AAB"), set("end_suffix", "B")]).unwrap();
+        assert_eq!(report.a.params.to_string(), "start=AAB, end=ZZZ");
+        assert_eq!(report.a.answer, "1");
+        assert_eq!(report.b.params.to_string(), "start_suffix=A, end_suffix=B");
+        assert_eq!(report.b.answer, "1");
+    }
+}
@@ -0,0 +1,99 @@
+//! Bare-bones SVG writer for exporting grid days as images. Only knows about the handful of
+//! shapes the days actually draw; everything is in user units, and it's up to the caller to
+//! decide how big a grid cell is.
+
+use std::fmt::{Display, Formatter};
+
+pub struct Svg {
+    width: f64,
+    height: f64,
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub fn new(width: f64, height: f64) -> Self {
+        Svg {
+            width,
+            height,
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: &str) {
+        self.elements.push(format!(
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="{fill}"/>"#
+        ));
+    }
+
+    pub fn circle(&mut self, cx: f64, cy: f64, r: f64, fill: &str) {
+        self.elements.push(format!(
+            r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="{fill}"/>"#
+        ));
+    }
+
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), stroke: &str, stroke_width: f64) {
+        let ((x1, y1), (x2, y2)) = (from, to);
+        self.elements.push(format!(
+            r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{stroke}" stroke-width="{stroke_width}"/>"#
+        ));
+    }
+
+    pub fn polyline(&mut self, points: &[(f64, f64)], stroke: &str, stroke_width: f64) {
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+        self.elements.push(format!(
+            r#"<polyline points="{}" fill="none" stroke="{stroke}" stroke-width="{stroke_width}" stroke-linejoin="round"/>"#,
+            points.join(" ")
+        ));
+    }
+
+    pub fn text(&mut self, x: f64, y: f64, size: f64, content: &str) {
+        self.elements.push(format!(
+            r#"<text x="{x}" y="{y}" font-family="monospace" font-size="{size}">{}</text>"#,
+            escape(content)
+        ));
+    }
+}
+
+impl Display for Svg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.width, self.height
+        )?;
+        writeln!(f, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+        for element in self.elements.iter() {
+            writeln!(f, "{}", element)?;
+        }
+        writeln!(f, "</svg>")
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_document() {
+        let mut svg = Svg::new(20.0, 10.0);
+        svg.rect(0.0, 0.0, 10.0, 10.0, "black");
+        svg.polyline(&[(0.0, 0.0), (5.0, 5.5)], "red", 1.0);
+        svg.text(1.0, 2.0, 4.0, "a<b");
+
+        let out = svg.to_string();
+
+        assert!(
+            out.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10""#)
+        );
+        assert!(out.contains(r#"<rect x="0" y="0" width="10" height="10" fill="black"/>"#));
+        assert!(out.contains(r#"points="0,0 5,5.5""#));
+        assert!(out.contains(">a&lt;b</text>"));
+        assert!(out.ends_with("</svg>\n"));
+    }
+}